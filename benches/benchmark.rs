@@ -1,5 +1,5 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use mirror_hash::Mirror256;
+use mirror_hash::{Mirror256, TreeHasher};
 use rand::{Rng, thread_rng};
 
 fn random_alphanumeric_string(length: usize) -> String {
@@ -15,7 +15,7 @@ fn hash_short_string(c: &mut Criterion) {
     let input = "This is a short test message.";
     c.bench_function("hash short string", |b| {
         b.iter(|| {
-            let hasher = Mirror256::new(Some(black_box(input)), None, None, true);
+            let hasher = Mirror256::new_from_bytes(black_box(input.as_bytes()));
             black_box(hasher.hexdigest())
         })
     });
@@ -25,7 +25,7 @@ fn hash_medium_string(c: &mut Criterion) {
     let input = "This is a medium length test message with some additional content to make it longer.";
     c.bench_function("hash medium string", |b| {
         b.iter(|| {
-            let hasher = Mirror256::new(Some(black_box(input)), None, None, true);
+            let hasher = Mirror256::new_from_bytes(black_box(input.as_bytes()));
             black_box(hasher.hexdigest())
         })
     });
@@ -35,7 +35,7 @@ fn hash_long_string(c: &mut Criterion) {
     let input = random_alphanumeric_string(1024);
     c.bench_function("hash long string (1KB)", |b| {
         b.iter(|| {
-            let hasher = Mirror256::new(Some(black_box(&input)), None, None, true);
+            let hasher = Mirror256::new_from_bytes(black_box(input.as_bytes()));
             black_box(hasher.hexdigest())
         })
     });
@@ -44,7 +44,7 @@ fn hash_long_string(c: &mut Criterion) {
 fn hash_empty_string(c: &mut Criterion) {
     c.bench_function("hash empty string", |b| {
         b.iter(|| {
-            let hasher = Mirror256::new(Some(black_box("")), None, None, true);
+            let hasher = Mirror256::new_from_bytes(black_box(b""));
             black_box(hasher.hexdigest())
         })
     });
@@ -53,21 +53,42 @@ fn hash_empty_string(c: &mut Criterion) {
 fn hash_update_multiple(c: &mut Criterion) {
     c.bench_function("hash update multiple", |b| {
         b.iter(|| {
-            let mut hasher = Mirror256::new(None, None, None, true);
-            hasher.update(black_box("part1"));
-            hasher.update(black_box("part2"));
-            hasher.update(black_box("part3"));
+            let mut hasher = Mirror256::new();
+            hasher.update(black_box(b"part1"));
+            hasher.update(black_box(b"part2"));
+            hasher.update(black_box(b"part3"));
             black_box(hasher.hexdigest())
         })
     });
 }
 
+fn hash_many_records(c: &mut Criterion) {
+    let records: Vec<String> = (0..256).map(|_| random_alphanumeric_string(32)).collect();
+    let messages: Vec<&[u8]> = records.iter().map(|r| r.as_bytes()).collect();
+    c.bench_function("hash many 32-byte records (256)", |b| {
+        b.iter(|| black_box(mirror_hash::hash_many(black_box(&messages))))
+    });
+}
+
+fn tree_hash_1mb(c: &mut Criterion) {
+    let input = random_alphanumeric_string(1 << 20);
+    c.bench_function("tree hash (1MB)", |b| {
+        b.iter(|| {
+            let mut tree = TreeHasher::default();
+            tree.update(black_box(input.as_bytes()));
+            black_box(tree.finalize())
+        })
+    });
+}
+
 criterion_group!(
     benches,
     hash_short_string,
     hash_medium_string,
     hash_long_string,
     hash_empty_string,
-    hash_update_multiple
+    hash_update_multiple,
+    hash_many_records,
+    tree_hash_1mb
 );
 criterion_main!(benches); 
\ No newline at end of file
@@ -1,5 +1,165 @@
+//! Generates the table behind the standard initial state
+//!
+//! Layer `i` of the standard state is taken from the fractional part of the cube
+//! root of the `i`-th prime (2, 3, 5, ...), as SHA-2 takes its constants from the
+//! first primes. The roots are computed exactly with integer arithmetic, so the
+//! table does not depend on floating point: `floor(cbrt(p) * 2^FRACTION_BITS)` is
+//! the integer cube root of `p * 2^(3 * FRACTION_BITS)`, found digit by digit in
+//! base 8, where each step brings down one base-8 digit of the radicand and settles
+//! one bit of the root.
+
+use std::cmp::Ordering;
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// Number of primes, one per layer; matches `MAX_DEPTH`
+const PRIMES: usize = 512;
+
+/// Fractional bits kept per root, one per wire; matches `MAX_SIZE`
+const FRACTION_BITS: usize = 1024;
+
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
-    println!("cargo:rerun-if-changed=src/lib.rs");
-    println!("cargo:rerun-if-changed=src/main.rs");
-} 
\ No newline at end of file
+
+    let mut table = String::new();
+    writeln!(table, "/// Fractional bits of the cube roots of the first {} primes, most significant word first", PRIMES).unwrap();
+    writeln!(table, "static PRIME_CUBE_ROOTS: [[u64; {}]; {}] = [", FRACTION_BITS / 64, PRIMES).unwrap();
+    for p in first_primes(PRIMES) {
+        let words = cube_root_fraction(p);
+        let words: Vec<String> = words.iter().map(|w| format!("0x{:016x}", w)).collect();
+        writeln!(table, "    [{}],", words.join(", ")).unwrap();
+    }
+    writeln!(table, "];").unwrap();
+
+    let out_dir = env::var("OUT_DIR").expect("cargo sets OUT_DIR");
+    fs::write(Path::new(&out_dir).join("prime_cube_roots.rs"), table).expect("write generated table");
+}
+
+/// The first `n` primes, by trial division
+fn first_primes(n: usize) -> Vec<u64> {
+    let mut primes: Vec<u64> = Vec::with_capacity(n);
+    let mut candidate = 2;
+    while primes.len() < n {
+        if primes.iter().take_while(|&&q| q * q <= candidate).all(|&q| candidate % q != 0) {
+            primes.push(candidate);
+        }
+        candidate += 1;
+    }
+    primes
+}
+
+/// The `FRACTION_BITS` bits after the binary point of `cbrt(p)`, as big-endian words
+fn cube_root_fraction(p: u64) -> Vec<u64> {
+    // Base-8 digits of the radicand p * 2^(3 * FRACTION_BITS): those of p, then zeros
+    let mut digits = Vec::new();
+    let mut high = p;
+    while high > 0 {
+        digits.push((high & 7) as u32);
+        high >>= 3;
+    }
+    digits.reverse();
+    digits.resize(digits.len() + FRACTION_BITS, 0);
+
+    // Invariants: remainder = radicand so far - root^3, square = root^2
+    let mut root = Big::zero();
+    let mut square = Big::zero();
+    let mut remainder = Big::zero();
+    for digit in digits {
+        // Doubling the root multiplies its cube by 8; (2r + 1)^3 - (2r)^3 = 12r^2 + 6r + 1
+        remainder = remainder.shl(3).add(&Big::from(u64::from(digit)));
+        let step = square.mul_small(12).add(&root.mul_small(6)).add(&Big::from(1));
+        if remainder.cmp(&step) != Ordering::Less {
+            remainder = remainder.sub(&step);
+            square = square.shl(2).add(&root.shl(2)).add(&Big::from(1));
+            root = root.shl(1).add(&Big::from(1));
+        } else {
+            square = square.shl(2);
+            root = root.shl(1);
+        }
+    }
+
+    // The low FRACTION_BITS bits of the root are the fraction
+    (0..FRACTION_BITS / 64).rev().map(|k| root.word(k)).collect()
+}
+
+/// Unsigned integer as little-endian 64-bit words without leading zero words
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Big(Vec<u64>);
+
+impl Big {
+    fn zero() -> Self {
+        Big(Vec::new())
+    }
+
+    fn from(value: u64) -> Self {
+        Big(vec![value]).trimmed()
+    }
+
+    fn word(&self, k: usize) -> u64 {
+        self.0.get(k).copied().unwrap_or(0)
+    }
+
+    fn trimmed(mut self) -> Self {
+        while self.0.last() == Some(&0) {
+            self.0.pop();
+        }
+        self
+    }
+
+    fn shl(&self, by: u32) -> Self {
+        let mut words = Vec::with_capacity(self.0.len() + 1);
+        let mut carry = 0;
+        for &w in &self.0 {
+            words.push((w << by) | carry);
+            carry = w >> (64 - by);
+        }
+        words.push(carry);
+        Big(words).trimmed()
+    }
+
+    fn add(&self, other: &Big) -> Self {
+        let len = self.0.len().max(other.0.len());
+        let mut words = Vec::with_capacity(len + 1);
+        let mut carry = false;
+        for k in 0..len {
+            let (sum, c1) = self.word(k).overflowing_add(other.word(k));
+            let (sum, c2) = sum.overflowing_add(u64::from(carry));
+            words.push(sum);
+            carry = c1 || c2;
+        }
+        words.push(u64::from(carry));
+        Big(words).trimmed()
+    }
+
+    /// `self - other`, which must not be negative
+    fn sub(&self, other: &Big) -> Self {
+        let mut words = Vec::with_capacity(self.0.len());
+        let mut borrow = false;
+        for k in 0..self.0.len() {
+            let (diff, b1) = self.word(k).overflowing_sub(other.word(k));
+            let (diff, b2) = diff.overflowing_sub(u64::from(borrow));
+            words.push(diff);
+            borrow = b1 || b2;
+        }
+        assert!(!borrow, "negative difference");
+        Big(words).trimmed()
+    }
+
+    fn mul_small(&self, factor: u64) -> Self {
+        let mut words = Vec::with_capacity(self.0.len() + 1);
+        let mut carry = 0u128;
+        for &w in &self.0 {
+            let product = u128::from(w) * u128::from(factor) + carry;
+            words.push(product as u64);
+            carry = product >> 64;
+        }
+        words.push(carry as u64);
+        Big(words).trimmed()
+    }
+
+    fn cmp(&self, other: &Big) -> Ordering {
+        self.0.len().cmp(&other.0.len()).then_with(|| self.0.iter().rev().cmp(other.0.iter().rev()))
+    }
+}
@@ -0,0 +1,137 @@
+//! Authenticated encryption over the duplex
+//!
+//! `MirrorAead` follows SpongeWrap on `MirrorDuplex`. The key, the nonce, the
+//! associated data and the plaintext are each cut into blocks of `BLOCK` bytes, at
+//! least one per string, and every block is duplexed with a frame byte appended that
+//! names its string and marks its last block. Each plaintext block is encrypted with
+//! the output of the call before it, and the output of the last call is the tag.
+//!
+//! The default duplex runs all `MAX_DEPTH` layers of the standard state, which the
+//! `block` module explains are needed to mix it. SpongeWrap overwrites the rate with
+//! each ciphertext block, so a change that stayed in the rate would vanish from the tag.
+
+use crate::mac::constant_time_eq;
+use crate::{Mirror256Error, MirrorDuplex};
+
+/// Payload bytes per duplex call: the duplex input less the frame byte
+const BLOCK: usize = 14;
+
+/// Frame bytes naming the string a block belongs to
+const KEY_FRAME: u8 = 0x01;
+const NONCE_FRAME: u8 = 0x02;
+const AD_FRAME: u8 = 0x03;
+const TEXT_FRAME: u8 = 0x04;
+
+/// Set in the frame byte of the last block of a string
+const LAST_BLOCK: u8 = 0x80;
+
+/// Authenticated encryption with associated data over a 256-bit duplex
+///
+/// ```
+/// use mirror_hash::MirrorAead;
+///
+/// let aead = MirrorAead::new(&[7; MirrorAead::KEY_BYTES]).unwrap();
+/// let nonce = [1; MirrorAead::NONCE_BYTES];
+/// let sealed = aead.seal(&nonce, b"header", b"secret message").unwrap();
+/// assert_eq!(sealed.len(), 14 + MirrorAead::TAG_BYTES);
+/// assert_eq!(aead.open(&nonce, b"header", &sealed).unwrap(), b"secret message");
+/// ```
+#[derive(Clone)]
+pub struct MirrorAead {
+    /// Duplex that has absorbed the key
+    keyed: MirrorDuplex,
+}
+
+impl MirrorAead {
+    /// Key length in bytes
+    pub const KEY_BYTES: usize = 16;
+    /// Nonce length in bytes; a nonce must never be reused with the same key
+    pub const NONCE_BYTES: usize = 16;
+    /// Length of the tag appended to the ciphertext
+    pub const TAG_BYTES: usize = 16;
+
+    /// AEAD under a `KEY_BYTES`-byte key
+    pub fn new(key: &[u8]) -> Result<Self, Mirror256Error> {
+        if key.len() != Self::KEY_BYTES {
+            return Err(Mirror256Error::KeyLength { len: key.len(), expected: Self::KEY_BYTES });
+        }
+        let mut keyed = MirrorDuplex::new();
+        absorb(&mut keyed, key, KEY_FRAME);
+        Ok(MirrorAead { keyed })
+    }
+
+    /// Encrypt `plaintext` and authenticate it with `associated_data`, returning the
+    /// ciphertext followed by the tag
+    pub fn seal(&self, nonce: &[u8], associated_data: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Mirror256Error> {
+        let (mut duplex, mut stream) = self.start(nonce, associated_data)?;
+        let mut sealed = Vec::with_capacity(plaintext.len() + Self::TAG_BYTES);
+        for_each_block(plaintext, |block, last| {
+            sealed.extend(block.iter().zip(&stream).map(|(p, z)| p ^ z));
+            duplex.duplex(&framed(block, TEXT_FRAME | last), &mut stream);
+        });
+        sealed.extend_from_slice(&stream);
+        Ok(sealed)
+    }
+
+    /// Check the tag of `sealed`, the output of `seal`, and return the plaintext
+    ///
+    /// The tag is compared in constant time, and no plaintext is returned unless it
+    /// matches.
+    pub fn open(&self, nonce: &[u8], associated_data: &[u8], sealed: &[u8]) -> Result<Vec<u8>, Mirror256Error> {
+        let Some(text_len) = sealed.len().checked_sub(Self::TAG_BYTES) else {
+            return Err(Mirror256Error::CiphertextTooShort { len: sealed.len(), min: Self::TAG_BYTES });
+        };
+        let (ciphertext, tag) = sealed.split_at(text_len);
+
+        let (mut duplex, mut stream) = self.start(nonce, associated_data)?;
+        let mut plaintext = Vec::with_capacity(text_len);
+        for_each_block(ciphertext, |block, last| {
+            let start = plaintext.len();
+            plaintext.extend(block.iter().zip(&stream).map(|(c, z)| c ^ z));
+            duplex.duplex(&framed(&plaintext[start..], TEXT_FRAME | last), &mut stream);
+        });
+
+        if constant_time_eq(&stream, tag) {
+            Ok(plaintext)
+        } else {
+            plaintext.iter_mut().for_each(|byte| *byte = 0);
+            Err(Mirror256Error::MacMismatch)
+        }
+    }
+
+    /// Absorb the nonce and associated data, returning the duplex and the key stream
+    /// of the first text block
+    fn start(&self, nonce: &[u8], associated_data: &[u8]) -> Result<(MirrorDuplex, [u8; Self::TAG_BYTES]), Mirror256Error> {
+        if nonce.len() != Self::NONCE_BYTES {
+            return Err(Mirror256Error::NonceLength { len: nonce.len(), expected: Self::NONCE_BYTES });
+        }
+        let mut duplex = self.keyed.clone();
+        absorb(&mut duplex, nonce, NONCE_FRAME);
+        let stream = absorb(&mut duplex, associated_data, AD_FRAME);
+        Ok((duplex, stream))
+    }
+}
+
+/// Call `f` with each block of `data` and `LAST_BLOCK` or 0; empty data is one empty block
+fn for_each_block(data: &[u8], mut f: impl FnMut(&[u8], u8)) {
+    let blocks = data.len().div_ceil(BLOCK).max(1);
+    for i in 0..blocks {
+        let block = &data[i * BLOCK..((i + 1) * BLOCK).min(data.len())];
+        let last = if i + 1 == blocks { LAST_BLOCK } else { 0 };
+        f(block, last);
+    }
+}
+
+/// A block with its frame byte appended
+fn framed(block: &[u8], frame: u8) -> Vec<u8> {
+    let mut input = block.to_vec();
+    input.push(frame);
+    input
+}
+
+/// Duplex every block of a string, returning the output of the last call
+fn absorb(duplex: &mut MirrorDuplex, data: &[u8], kind: u8) -> [u8; MirrorAead::TAG_BYTES] {
+    let mut output = [0; MirrorAead::TAG_BYTES];
+    for_each_block(data, |block, last| duplex.duplex(&framed(block, kind | last), &mut output));
+    output
+}
@@ -0,0 +1,111 @@
+//! The block permutation as a standalone, invertible map
+//!
+//! Hashing a block XORs each layer encoding into the state and runs it through two
+//! sublayers of Toffoli and Fredkin gates. Every gate is its own inverse and the gates
+//! of a sublayer touch disjoint wires, so for fixed encodings the map from block to
+//! output is a bijection, undone by running the layers backwards.
+//!
+//! Diffusion is slow. One 128-layer pass moves a flipped bit only a few bytes, while
+//! `MAX_DEPTH` = 512 layers spread it over the whole 256-bit state, so constructions
+//! that need one call to mix the state run all `MAX_DEPTH` layers: the sponge and
+//! duplex defaults, `MirrorAead` and `MirrorCipher`.
+
+use crate::builder::{check_depth, check_size};
+use crate::permutation::{self, LayerCode, Width, MAX_WORDS};
+use crate::schedule::GateSchedule;
+use crate::Mirror256Error;
+
+/// The permutation of one block under a fixed set of layer encodings
+///
+/// ```
+/// use mirror_hash::Mirror256;
+///
+/// let hasher = Mirror256::new();
+/// let permutation = hasher.block_permutation();
+///
+/// let mut state = [7u8; 32];
+/// permutation.forward(&mut state);
+/// permutation.inverse(&mut state);
+/// assert_eq!(state, [7u8; 32]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BlockPermutation {
+    width: Width,
+    codes: Vec<LayerCode>,
+}
+
+impl BlockPermutation {
+    /// Permutation of a `size`-bit state with one layer per encoding, layer 0 first
+    ///
+    /// Each encoding is `size / 8` bytes in the byte order of blocks and digests, so the
+    /// digest of one block can serve as an encoding of the next.
+    pub fn new(size: usize, encodings: &[&[u8]]) -> Result<Self, Mirror256Error> {
+        check_depth(encodings.len())?;
+        check_size(size)?;
+
+        let width = Width::new(size);
+        let codes = encodings
+            .iter()
+            .map(|encoding| {
+                if encoding.len() != size / 8 {
+                    return Err(Mirror256Error::EncodingLength { len: encoding.len(), expected: size / 8 });
+                }
+                let mut words = [0u64; MAX_WORDS];
+                permutation::unpack(width, encoding, &mut words);
+                Ok(LayerCode::compile(width, &words))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(BlockPermutation { width, codes })
+    }
+
+    /// The layers a hasher applies to its next block
+    pub(crate) fn from_schedule(width: Width, schedule: &GateSchedule) -> Self {
+        BlockPermutation {
+            width,
+            codes: schedule.layers().cloned().collect(),
+        }
+    }
+
+    /// State size in bits
+    pub fn size(&self) -> usize {
+        self.width.bits()
+    }
+
+    /// Number of layers
+    pub fn depth(&self) -> usize {
+        self.codes.len()
+    }
+
+    /// The layer encodings, layer 0 first
+    pub fn encodings(&self) -> Vec<Vec<u8>> {
+        self.codes.iter().map(|code| permutation::pack(self.width, code.encoding())).collect()
+    }
+
+    /// Run a state of `size / 8` bytes through every layer, layer 0 first
+    ///
+    /// # Panics
+    ///
+    /// If the state is not `size / 8` bytes long.
+    pub fn forward(&self, state: &mut [u8]) {
+        self.map(state, |words| permutation::permute(self.width, words, &self.codes));
+    }
+
+    /// Undo `forward`, running the layers backwards
+    ///
+    /// # Panics
+    ///
+    /// If the state is not `size / 8` bytes long.
+    pub fn inverse(&self, state: &mut [u8]) {
+        self.map(state, |words| permutation::unpermute(self.width, words, &self.codes));
+    }
+
+    /// Apply `f` to the words of a byte state
+    fn map(&self, state: &mut [u8], f: impl FnOnce(&mut [u64])) {
+        assert_eq!(state.len(), self.size() / 8, "state must be one block long");
+        let mut words = [0u64; MAX_WORDS];
+        permutation::unpack(self.width, state, &mut words);
+        f(&mut words);
+        state.copy_from_slice(&permutation::pack(self.width, &words));
+    }
+}
@@ -0,0 +1,300 @@
+use crate::{Compression, KeySchedule, Mirror256, Mirror256Error, Padding, DEFAULT_DEPTH, DEFAULT_SIZE};
+use std::fmt;
+
+/// Largest supported number of layers
+pub const MAX_DEPTH: usize = 512;
+
+/// Largest supported state size in bits
+pub const MAX_SIZE: usize = 1024;
+
+/// Smallest supported state size in bits; its 16-byte blocks still hold the
+/// 64-bit length field of the padding
+///
+/// A truncated digest length takes the last 8 bytes of the parameter block, so the
+/// personalization of a truncated hasher must leave them free: at this size it is
+/// limited to 8 bytes.
+pub const MIN_SIZE: usize = 128;
+
+/// Smallest supported digest length in bits
+pub const MIN_OUTPUT_SIZE: usize = 64;
+
+/// Longest supported personalization string in bytes, less for truncated digests of
+/// states under 192 bits
+pub const PERSONALIZATION_BYTES: usize = 16;
+
+/// Longest supported salt in bytes
+pub const SALT_BYTES: usize = 16;
+
+/// Source of the layer encodings used before any block has been hashed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum InitialState {
+    /// Encodings derived from the cubic roots of the first primes
+    #[default]
+    Standard,
+    /// Encodings drawn from SplitMix64 with the given seed: word `k` of layer `i`
+    /// is output number `i * words + k`, where `words` is the number of 64-bit words
+    /// covering the state, and bits beyond the state size are dropped
+    Seeded(u64),
+}
+
+/// Seed of the seeded initial state used by the original implementation
+pub const DEFAULT_SEED: u64 = 777;
+
+/// Named parameter sets
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preset {
+    /// 128 layers over a 256-bit state, the reference parameters
+    Standard,
+    /// 32 layers over a 256-bit state, for experiments where speed matters more than margin
+    Fast,
+    /// 256 layers over a 256-bit state, twice the reference depth
+    Conservative,
+}
+
+impl Preset {
+    /// Number of layers of the preset
+    pub fn depth(self) -> usize {
+        match self {
+            Preset::Standard => DEFAULT_DEPTH,
+            Preset::Fast => 32,
+            Preset::Conservative => 256,
+        }
+    }
+
+    /// State size in bits of the preset
+    pub fn size(self) -> usize {
+        DEFAULT_SIZE
+    }
+}
+
+/// Validating builder for configured Mirror256 hashers
+///
+/// ```
+/// use mirror_hash::{InitialState, Mirror256Builder, DEFAULT_SEED};
+///
+/// let mut hasher = Mirror256Builder::new()
+///     .depth(64)
+///     .initial_state(InitialState::Seeded(DEFAULT_SEED))
+///     .personalization(b"my-app v1")
+///     .build()
+///     .unwrap();
+/// hasher.update(b"data");
+/// println!("{}", hasher.hexdigest());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Mirror256Builder {
+    pub(crate) depth: usize,
+    pub(crate) size: usize,
+    pub(crate) output_size: Option<usize>,
+    pub(crate) initial_state: InitialState,
+    pub(crate) padding: Padding,
+    pub(crate) compression: Compression,
+    pub(crate) key_schedule: KeySchedule,
+    pub(crate) initial_value: Option<Vec<Vec<u8>>>,
+    pub(crate) personalization: Vec<u8>,
+    pub(crate) salt: Vec<u8>,
+    pub(crate) key: Key,
+}
+
+/// Key bytes that stay out of `Debug` output
+#[derive(Clone, Default, PartialEq, Eq)]
+pub(crate) struct Key(pub(crate) Vec<u8>);
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Key({} bytes)", self.0.len())
+    }
+}
+
+impl Default for Mirror256Builder {
+    fn default() -> Self {
+        Self::preset(Preset::Standard)
+    }
+}
+
+impl Mirror256Builder {
+    /// Start from the standard parameters
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from a named parameter set
+    pub fn preset(preset: Preset) -> Self {
+        Mirror256Builder {
+            depth: preset.depth(),
+            size: preset.size(),
+            output_size: None,
+            initial_state: InitialState::default(),
+            padding: Padding::default(),
+            compression: Compression::default(),
+            key_schedule: KeySchedule::default(),
+            initial_value: None,
+            personalization: Vec::new(),
+            salt: Vec::new(),
+            key: Key::default(),
+        }
+    }
+
+    /// Set the number of layers
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Set the state size in bits
+    pub fn size(mut self, size: usize) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Set the digest length in bits, which defaults to the state size
+    ///
+    /// A shorter digest makes the hasher wide-pipe: chaining values keep the whole
+    /// state and finalization runs an output transformation before truncating. The
+    /// digest length is also mixed into the initial state, so hashers that differ
+    /// only in digest length are unrelated functions.
+    pub fn output_size(mut self, bits: usize) -> Self {
+        self.output_size = Some(bits);
+        self
+    }
+
+    /// Set the source of the initial layer encodings
+    pub fn initial_state(mut self, initial_state: InitialState) -> Self {
+        self.initial_state = initial_state;
+        self
+    }
+
+    /// Use explicit initial layer encodings instead of an `InitialState`, layer 0
+    /// first, each `size / 8` bytes in the byte order of digests
+    ///
+    /// This also sets the depth to the number of encodings. The encodings of an
+    /// existing hasher are available from `BlockPermutation::encodings`.
+    pub fn initial_value<E: AsRef<[u8]>>(mut self, encodings: &[E]) -> Self {
+        self.depth = encodings.len();
+        self.initial_value = Some(encodings.iter().map(|e| e.as_ref().to_vec()).collect());
+        self
+    }
+
+    /// Set the padding rule applied at finalization
+    pub fn padding(mut self, padding: Padding) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Set how each block's permutation output becomes the next chaining value
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Set where the layer encodings of each block come from
+    pub fn key_schedule(mut self, key_schedule: KeySchedule) -> Self {
+        self.key_schedule = key_schedule;
+        self
+    }
+
+    /// Set a personalization string that is absorbed into the initial state,
+    /// so that hashers with different strings are independent functions
+    ///
+    /// It may not reach the last 8 bytes of a state block when the digest is
+    /// truncated, since those hold the digest length.
+    pub fn personalization(mut self, personalization: &[u8]) -> Self {
+        self.personalization = personalization.to_vec();
+        self
+    }
+
+    /// Set a salt that is absorbed into the initial state next to the
+    /// personalization, for randomized hashing; it needs at least two layers
+    pub fn salt(mut self, salt: &[u8]) -> Self {
+        self.salt = salt.to_vec();
+        self
+    }
+
+    /// Set a secret key for the native keyed mode, a MAC and PRF in one pass
+    ///
+    /// Every initial layer encoding is derived from the key with the unkeyed
+    /// parameters, so the key replaces the initial state and keyed digests are
+    /// unrelated to unkeyed ones. Keyed digests, also at full width, come from the
+    /// output transformation, whose `2 * size` layers spread a change in the message
+    /// over the whole digest; the last chaining value alone would not (see the
+    /// `block` module). An empty key means unkeyed hashing.
+    pub fn key(mut self, key: &[u8]) -> Self {
+        self.key = Key(key.to_vec());
+        self
+    }
+
+    /// Validate the parameters and create the hasher
+    pub fn build(&self) -> Result<Mirror256, Mirror256Error> {
+        check_depth(self.depth)?;
+        check_size(self.size)?;
+        if let Some(bits) = self.output_size {
+            check_output_size(bits, self.size)?;
+        }
+        let max_personalization = match self.output_size {
+            // The digest length takes the last 8 bytes of the parameter block
+            Some(bits) if bits < self.size => PERSONALIZATION_BYTES.min(self.size / 8 - 8),
+            _ => PERSONALIZATION_BYTES,
+        };
+        if self.personalization.len() > max_personalization {
+            return Err(Mirror256Error::PersonalizationTooLong {
+                len: self.personalization.len(),
+                max: max_personalization,
+            });
+        }
+        if self.salt.len() > SALT_BYTES {
+            return Err(Mirror256Error::SaltTooLong { len: self.salt.len(), max: SALT_BYTES });
+        }
+        if !self.salt.is_empty() && self.depth < 2 {
+            return Err(Mirror256Error::SaltNeedsTwoLayers);
+        }
+        if let Some(encodings) = &self.initial_value {
+            if encodings.len() != self.depth {
+                return Err(Mirror256Error::InitialValueDepth { layers: encodings.len(), depth: self.depth });
+            }
+            if let Some(encoding) = encodings.iter().find(|e| e.len() != self.size / 8) {
+                return Err(Mirror256Error::EncodingLength { len: encoding.len(), expected: self.size / 8 });
+            }
+        }
+
+        Ok(Mirror256::from_builder(self))
+    }
+}
+
+/// Check a number of layers against the supported range
+pub(crate) fn check_depth(depth: usize) -> Result<(), Mirror256Error> {
+    if depth == 0 {
+        return Err(Mirror256Error::ZeroDepth);
+    }
+    if depth > MAX_DEPTH {
+        return Err(Mirror256Error::DepthTooLarge { depth, max: MAX_DEPTH });
+    }
+    Ok(())
+}
+
+/// Check a digest length in bits against the supported range for a state size
+fn check_output_size(bits: usize, size: usize) -> Result<(), Mirror256Error> {
+    if !bits.is_multiple_of(8) {
+        return Err(Mirror256Error::OutputSizeNotMultipleOf8(bits));
+    }
+    if bits < MIN_OUTPUT_SIZE {
+        return Err(Mirror256Error::OutputSizeTooSmall { bits, min: MIN_OUTPUT_SIZE });
+    }
+    if bits > size {
+        return Err(Mirror256Error::OutputSizeTooLarge { bits, max: size });
+    }
+    Ok(())
+}
+
+/// Check a state size in bits against the supported range
+pub(crate) fn check_size(size: usize) -> Result<(), Mirror256Error> {
+    if !size.is_multiple_of(8) {
+        return Err(Mirror256Error::SizeNotMultipleOf8(size));
+    }
+    if size < MIN_SIZE {
+        return Err(Mirror256Error::SizeTooSmall { size, min: MIN_SIZE });
+    }
+    if size > MAX_SIZE {
+        return Err(Mirror256Error::SizeTooLarge { size, max: MAX_SIZE });
+    }
+    Ok(())
+}
@@ -0,0 +1,150 @@
+//! Block cipher from the keyed gate network
+//!
+//! For fixed layer encodings the block permutation is a bijection whose inverse runs
+//! the layers backwards, so encodings derived from a key make it a block cipher on
+//! 256-bit blocks. The key schedule derives all `MAX_DEPTH` layers, layer `i` being
+//! the Miyaguchi–Preneel Mirror256 digest of `"Mirror256 cipher layer"`, `i` as a
+//! 32-bit big-endian integer, the key and layer `i - 1`, so a leaked layer does not
+//! reveal the key, and the other layers still need it.
+//!
+//! The CTR and CBC modes do not authenticate; `MirrorAead` does.
+
+use crate::mac::derive_layers;
+use crate::{BlockPermutation, Compression, Mirror256, Mirror256Error, MAX_DEPTH};
+
+/// Prefix of the messages that derive cipher layer encodings
+const CIPHER_LAYER_DOMAIN: &[u8] = b"Mirror256 cipher layer";
+
+/// Block cipher on 256-bit blocks under a 256-bit key
+///
+/// ```
+/// use mirror_hash::MirrorCipher;
+///
+/// let cipher = MirrorCipher::new(&[9; MirrorCipher::KEY_BYTES]).unwrap();
+/// let mut block = *b"thirty-two bytes of one block!!!";
+/// cipher.encrypt_block(&mut block);
+/// cipher.decrypt_block(&mut block);
+/// assert_eq!(&block, b"thirty-two bytes of one block!!!");
+///
+/// let iv = [0; MirrorCipher::BLOCK_BYTES];
+/// let ciphertext = cipher.encrypt_cbc(&iv, b"any length").unwrap();
+/// assert_eq!(cipher.decrypt_cbc(&iv, &ciphertext).unwrap(), b"any length");
+/// ```
+#[derive(Clone)]
+pub struct MirrorCipher {
+    permutation: BlockPermutation,
+}
+
+impl MirrorCipher {
+    /// Key length in bytes
+    pub const KEY_BYTES: usize = 32;
+    /// Block length in bytes
+    pub const BLOCK_BYTES: usize = 32;
+    /// CTR nonce length in bytes; the rest of each counter block counts blocks
+    pub const NONCE_BYTES: usize = 16;
+
+    /// Cipher under a `KEY_BYTES`-byte key
+    pub fn new(key: &[u8]) -> Result<Self, Mirror256Error> {
+        if key.len() != Self::KEY_BYTES {
+            return Err(Mirror256Error::KeyLength { len: key.len(), expected: Self::KEY_BYTES });
+        }
+        let hasher = Mirror256::builder().compression(Compression::MiyaguchiPreneel).build()?;
+        let encodings = derive_layers(&hasher, CIPHER_LAYER_DOMAIN, key, MAX_DEPTH);
+        let encodings: Vec<&[u8]> = encodings.iter().map(Vec::as_slice).collect();
+        let permutation = BlockPermutation::new(8 * Self::BLOCK_BYTES, &encodings)?;
+        Ok(MirrorCipher { permutation })
+    }
+
+    /// Encrypt one block in place
+    ///
+    /// # Panics
+    ///
+    /// If the block is not `BLOCK_BYTES` long.
+    pub fn encrypt_block(&self, block: &mut [u8]) {
+        self.permutation.forward(block);
+    }
+
+    /// Decrypt one block in place
+    ///
+    /// # Panics
+    ///
+    /// If the block is not `BLOCK_BYTES` long.
+    pub fn decrypt_block(&self, block: &mut [u8]) {
+        self.permutation.inverse(block);
+    }
+
+    /// Encrypt in counter mode: XOR with the encryptions of `nonce` followed by the
+    /// block index as a 128-bit big-endian integer
+    ///
+    /// Any length is accepted and the ciphertext is as long as the plaintext. A nonce
+    /// must never be reused with the same key.
+    pub fn encrypt_ctr(&self, nonce: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Mirror256Error> {
+        if nonce.len() != Self::NONCE_BYTES {
+            return Err(Mirror256Error::NonceLength { len: nonce.len(), expected: Self::NONCE_BYTES });
+        }
+        let mut output = Vec::with_capacity(plaintext.len());
+        for (index, chunk) in plaintext.chunks(Self::BLOCK_BYTES).enumerate() {
+            let mut stream = [nonce, &(index as u128).to_be_bytes()].concat();
+            self.encrypt_block(&mut stream);
+            output.extend(chunk.iter().zip(&stream).map(|(p, z)| p ^ z));
+        }
+        Ok(output)
+    }
+
+    /// Decrypt in counter mode, which is the same as encrypting
+    pub fn decrypt_ctr(&self, nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, Mirror256Error> {
+        self.encrypt_ctr(nonce, ciphertext)
+    }
+
+    /// Encrypt in CBC mode after PKCS#7 padding, which adds 1 to `BLOCK_BYTES` bytes
+    ///
+    /// The IV is one block and must be unpredictable.
+    pub fn encrypt_cbc(&self, iv: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Mirror256Error> {
+        check_iv(iv)?;
+        let pad = Self::BLOCK_BYTES - plaintext.len() % Self::BLOCK_BYTES;
+        let mut output = plaintext.to_vec();
+        output.resize(plaintext.len() + pad, pad as u8);
+
+        let mut previous = iv.to_vec();
+        for block in output.chunks_mut(Self::BLOCK_BYTES) {
+            block.iter_mut().zip(&previous).for_each(|(b, p)| *b ^= p);
+            self.encrypt_block(block);
+            previous.copy_from_slice(block);
+        }
+        Ok(output)
+    }
+
+    /// Decrypt in CBC mode and remove the PKCS#7 padding
+    pub fn decrypt_cbc(&self, iv: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, Mirror256Error> {
+        check_iv(iv)?;
+        if ciphertext.is_empty() || !ciphertext.len().is_multiple_of(Self::BLOCK_BYTES) {
+            return Err(Mirror256Error::CiphertextLength { len: ciphertext.len(), block: Self::BLOCK_BYTES });
+        }
+
+        let mut output = ciphertext.to_vec();
+        let previous_blocks = [iv].into_iter().chain(ciphertext.chunks(Self::BLOCK_BYTES));
+        for (block, previous) in output.chunks_mut(Self::BLOCK_BYTES).zip(previous_blocks) {
+            self.decrypt_block(block);
+            block.iter_mut().zip(previous).for_each(|(b, p)| *b ^= p);
+        }
+
+        // Check every padding byte rather than stopping at the first bad one
+        let pad = usize::from(output[output.len() - 1]);
+        let mut bad = pad == 0 || pad > Self::BLOCK_BYTES;
+        for (i, &byte) in output[output.len() - Self::BLOCK_BYTES..].iter().rev().enumerate() {
+            bad |= i < pad && usize::from(byte) != pad;
+        }
+        if bad {
+            return Err(Mirror256Error::InvalidPadding);
+        }
+        output.truncate(output.len() - pad);
+        Ok(output)
+    }
+}
+
+fn check_iv(iv: &[u8]) -> Result<(), Mirror256Error> {
+    if iv.len() != MirrorCipher::BLOCK_BYTES {
+        return Err(Mirror256Error::IvLength { len: iv.len(), expected: MirrorCipher::BLOCK_BYTES });
+    }
+    Ok(())
+}
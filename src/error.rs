@@ -0,0 +1,141 @@
+use std::fmt;
+
+/// Errors returned when configuring a Mirror256 hasher
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mirror256Error {
+    /// The number of layers was zero
+    ZeroDepth,
+    /// The number of layers exceeds what the initial state can provide
+    DepthTooLarge { depth: usize, max: usize },
+    /// The state size in bits is not a whole number of bytes
+    SizeNotMultipleOf8(usize),
+    /// The state size in bits is below the supported minimum
+    SizeTooSmall { size: usize, min: usize },
+    /// The state size in bits exceeds the supported maximum
+    SizeTooLarge { size: usize, max: usize },
+    /// The personalization string does not fit in the parameter block
+    PersonalizationTooLong { len: usize, max: usize },
+    /// The salt does not fit in the parameter block
+    SaltTooLong { len: usize, max: usize },
+    /// A salt was given for a single-layer network, which has no layer to hold it
+    SaltNeedsTwoLayers,
+    /// The number of explicit initial encodings differs from the depth
+    InitialValueDepth { layers: usize, depth: usize },
+    /// A message authentication tag did not match
+    MacMismatch,
+    /// A key derivation asked for more output than it can produce
+    OutputTooLong { len: usize, max: usize },
+    /// A pseudo-random key is shorter than one digest
+    PseudoRandomKeyTooShort { len: usize, min: usize },
+    /// The leaf chunk size of a tree hash was zero
+    ZeroChunkSize,
+    /// A layer encoding is not one state long
+    EncodingLength { len: usize, expected: usize },
+    /// A password hashing cost parameter is out of range
+    PasswordParameterOutOfRange { name: &'static str, value: usize, min: usize, max: usize },
+    /// A password salt is shorter than the minimum
+    SaltTooShort { len: usize, min: usize },
+    /// A stored password hash is not a valid PHC string
+    MalformedPasswordHash,
+    /// A password did not match its stored hash
+    PasswordMismatch,
+    /// A sponge capacity is not a positive whole number of bytes leaving room for a rate
+    SpongeCapacity { capacity: usize, size: usize },
+    /// A key does not have the length the construction requires
+    KeyLength { len: usize, expected: usize },
+    /// A nonce does not have the length the construction requires
+    NonceLength { len: usize, expected: usize },
+    /// A sealed message is too short to hold its tag
+    CiphertextTooShort { len: usize, min: usize },
+    /// An IV does not have the length the mode requires
+    IvLength { len: usize, expected: usize },
+    /// A ciphertext is not a positive whole number of blocks
+    CiphertextLength { len: usize, block: usize },
+    /// Decrypted padding is malformed
+    InvalidPadding,
+    /// The digest length in bits is not a whole number of bytes
+    OutputSizeNotMultipleOf8(usize),
+    /// The digest length in bits is below the supported minimum
+    OutputSizeTooSmall { bits: usize, min: usize },
+    /// The digest length in bits exceeds the state size
+    OutputSizeTooLarge { bits: usize, max: usize },
+}
+
+impl fmt::Display for Mirror256Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mirror256Error::ZeroDepth => write!(f, "depth must be at least 1 layer"),
+            Mirror256Error::DepthTooLarge { depth, max } => {
+                write!(f, "depth of {} layers exceeds the maximum of {}", depth, max)
+            }
+            Mirror256Error::SizeNotMultipleOf8(size) => {
+                write!(f, "size of {} bits is not a multiple of 8", size)
+            }
+            Mirror256Error::SizeTooSmall { size, min } => {
+                write!(f, "size of {} bits is smaller than the minimum of {}", size, min)
+            }
+            Mirror256Error::SizeTooLarge { size, max } => {
+                write!(f, "size of {} bits exceeds the maximum of {}", size, max)
+            }
+            Mirror256Error::PersonalizationTooLong { len, max } => {
+                write!(f, "personalization of {} bytes exceeds the maximum of {}", len, max)
+            }
+            Mirror256Error::SaltTooLong { len, max } => {
+                write!(f, "salt of {} bytes exceeds the maximum of {}", len, max)
+            }
+            Mirror256Error::SaltNeedsTwoLayers => write!(f, "a salt needs a depth of at least 2 layers"),
+            Mirror256Error::InitialValueDepth { layers, depth } => {
+                write!(f, "initial value of {} layers does not match the depth of {}", layers, depth)
+            }
+            Mirror256Error::MacMismatch => write!(f, "authentication tag mismatch"),
+            Mirror256Error::OutputTooLong { len, max } => {
+                write!(f, "output of {} bytes exceeds the maximum of {}", len, max)
+            }
+            Mirror256Error::PseudoRandomKeyTooShort { len, min } => {
+                write!(f, "pseudo-random key of {} bytes is shorter than the minimum of {}", len, min)
+            }
+            Mirror256Error::ZeroChunkSize => write!(f, "tree leaf chunks must hold at least 1 byte"),
+            Mirror256Error::EncodingLength { len, expected } => {
+                write!(f, "layer encoding of {} bytes does not match the state size of {} bytes", len, expected)
+            }
+            Mirror256Error::PasswordParameterOutOfRange { name, value, min, max } => {
+                write!(f, "password parameter {}={} is outside the range {} to {}", name, value, min, max)
+            }
+            Mirror256Error::SaltTooShort { len, min } => {
+                write!(f, "salt of {} bytes is shorter than the minimum of {}", len, min)
+            }
+            Mirror256Error::MalformedPasswordHash => write!(f, "malformed password hash string"),
+            Mirror256Error::PasswordMismatch => write!(f, "password does not match"),
+            Mirror256Error::SpongeCapacity { capacity, size } => {
+                write!(f, "capacity of {} bits does not leave a rate of whole bytes in a {}-bit state", capacity, size)
+            }
+            Mirror256Error::KeyLength { len, expected } => {
+                write!(f, "key of {} bytes does not match the required {} bytes", len, expected)
+            }
+            Mirror256Error::NonceLength { len, expected } => {
+                write!(f, "nonce of {} bytes does not match the required {} bytes", len, expected)
+            }
+            Mirror256Error::CiphertextTooShort { len, min } => {
+                write!(f, "ciphertext of {} bytes is shorter than the {}-byte tag", len, min)
+            }
+            Mirror256Error::IvLength { len, expected } => {
+                write!(f, "IV of {} bytes does not match the required {} bytes", len, expected)
+            }
+            Mirror256Error::CiphertextLength { len, block } => {
+                write!(f, "ciphertext of {} bytes is not a positive multiple of the {}-byte block", len, block)
+            }
+            Mirror256Error::InvalidPadding => write!(f, "invalid padding"),
+            Mirror256Error::OutputSizeNotMultipleOf8(bits) => {
+                write!(f, "digest length of {} bits is not a multiple of 8", bits)
+            }
+            Mirror256Error::OutputSizeTooSmall { bits, min } => {
+                write!(f, "digest length of {} bits is smaller than the minimum of {}", bits, min)
+            }
+            Mirror256Error::OutputSizeTooLarge { bits, max } => {
+                write!(f, "digest length of {} bits exceeds the state size of {}", bits, max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Mirror256Error {}
@@ -0,0 +1,101 @@
+//! HKDF (RFC 5869) over HMAC-Mirror256
+//!
+//! `extract` condenses input keying material into a pseudo-random key of one digest;
+//! `expand` stretches a pseudo-random key into up to 255 digests of output, bound to
+//! an application-specific `info` string. The free functions use the standard
+//! Mirror256 parameters; `Hkdf` works with any configured hasher and digest length.
+//!
+//! ```
+//! let prk = mirror_hash::hkdf::extract(b"salt", b"master secret");
+//! let encryption_key = mirror_hash::hkdf::expand(&prk, b"encryption", 32).unwrap();
+//! let signing_key = mirror_hash::hkdf::expand(&prk, b"signing", 32).unwrap();
+//! assert_ne!(encryption_key, signing_key);
+//! ```
+
+use crate::{Digest, HmacMirror256, Mirror256, Mirror256Error};
+
+/// Most output blocks of `expand`, as the block counter is one byte
+const MAX_BLOCKS: usize = 255;
+
+/// HKDF over a configured hasher
+#[derive(Clone)]
+pub struct Hkdf {
+    hasher: Mirror256,
+}
+
+impl Default for Hkdf {
+    fn default() -> Self {
+        Self::with_hasher(&Mirror256::new())
+    }
+}
+
+impl Hkdf {
+    /// HKDF with HMAC over copies of `hasher`
+    pub fn with_hasher(hasher: &Mirror256) -> Self {
+        Hkdf { hasher: hasher.clone() }
+    }
+
+    /// Length of a pseudo-random key, and of each output block, in bytes
+    pub fn output_size(&self) -> usize {
+        self.hasher.output_size()
+    }
+
+    /// Longest output of `expand` in bytes
+    pub fn max_output(&self) -> usize {
+        MAX_BLOCKS * self.output_size()
+    }
+
+    /// Condense `ikm` into a pseudo-random key; an empty salt stands for a digest of zeros
+    pub fn extract(&self, salt: &[u8], ikm: &[u8]) -> Digest {
+        let mut mac = self.mac(salt);
+        mac.update(ikm);
+        mac.finalize()
+    }
+
+    /// Derive `length` bytes for `info` from a pseudo-random key of at least one digest
+    pub fn expand(&self, prk: &[u8], info: &[u8], length: usize) -> Result<Vec<u8>, Mirror256Error> {
+        if prk.len() < self.output_size() {
+            return Err(Mirror256Error::PseudoRandomKeyTooShort { len: prk.len(), min: self.output_size() });
+        }
+        if length > self.max_output() {
+            return Err(Mirror256Error::OutputTooLong { len: length, max: self.max_output() });
+        }
+
+        // T(i) = HMAC(PRK, T(i - 1) ‖ info ‖ i), with T(0) empty
+        let keyed = self.mac(prk);
+        let mut okm = Vec::with_capacity(length);
+        let mut block = Vec::new();
+        for counter in 1..=MAX_BLOCKS as u8 {
+            if okm.len() >= length {
+                break;
+            }
+            let mut mac = keyed.clone();
+            mac.update(&block);
+            mac.update(info);
+            mac.update(&[counter]);
+            block = mac.finalize();
+            okm.extend_from_slice(&block);
+        }
+        okm.truncate(length);
+        Ok(okm)
+    }
+
+    /// `extract` followed by `expand`
+    pub fn derive(&self, salt: &[u8], ikm: &[u8], info: &[u8], length: usize) -> Result<Vec<u8>, Mirror256Error> {
+        self.expand(&self.extract(salt, ikm), info, length)
+    }
+
+    fn mac(&self, key: &[u8]) -> HmacMirror256 {
+        HmacMirror256::with_hasher(&self.hasher, key).expect("HMAC accepts keys of any length")
+    }
+}
+
+/// `Hkdf::extract` with the standard parameters
+pub fn extract(salt: &[u8], ikm: &[u8]) -> Digest {
+    Hkdf::default().extract(salt, ikm)
+}
+
+/// `Hkdf::expand` with the standard parameters
+pub fn expand(prk: &[u8], info: &[u8], length: usize) -> Result<Vec<u8>, Mirror256Error> {
+    Hkdf::default().expand(prk, info, length)
+}
@@ -1,326 +1,457 @@
-use rand::{Rng, SeedableRng};
-use rand::rngs::StdRng;
+use permutation::{LayerCode, Width, MAX_WORDS};
+use schedule::GateSchedule;
+use splitmix::SplitMix64;
+
+mod aead;
+mod block;
+mod builder;
+mod cipher;
+mod error;
+pub mod hkdf;
+mod mac;
+mod multi;
+#[cfg(feature = "parallel")]
+mod parallel;
+pub mod password;
+mod permutation;
+#[cfg(test)]
+mod reference;
+#[cfg(feature = "digest")]
+mod rust_crypto;
+mod schedule;
+mod simd;
+mod splitmix;
+mod sponge;
+mod tree;
+mod variants;
+mod xof;
+
+pub use aead::MirrorAead;
+pub use block::BlockPermutation;
+pub use builder::{InitialState, Mirror256Builder, Preset, DEFAULT_SEED, MAX_DEPTH, MAX_SIZE, MIN_OUTPUT_SIZE, MIN_SIZE, PERSONALIZATION_BYTES, SALT_BYTES};
+pub use cipher::MirrorCipher;
+pub use error::Mirror256Error;
+pub use hkdf::Hkdf;
+pub use mac::HmacMirror256;
+#[cfg(feature = "digest")]
+pub use rust_crypto::Mirror256Core;
+pub use sponge::{MirrorDuplex, MirrorSponge, SpongeReader};
+pub use tree::{TreeHasher, DEFAULT_CHUNK_SIZE};
+pub use variants::{Mirror128, Mirror224, Mirror256_128, Mirror256_160, Mirror256_64, Mirror384, Mirror512};
+pub use xof::XofReader;
+#[cfg(feature = "digest")]
+pub use digest;
 
 const DEFAULT_DEPTH: usize = 128;
 const DEFAULT_SIZE: usize = 256;
 
-// Gate types
-const TOFFOLI: u8 = 0;
-const FREDKIN: u8 = 1;
+// Fractional cube-root bits of the first primes, generated by build.rs
+include!(concat!(env!("OUT_DIR"), "/prime_cube_roots.rs"));
 
-// Gate symmetry
-const REGULAR: u8 = 0;
-const MIRRORED: u8 = 1;
+// Every layer of every supported state has its own root
+const _: () = assert!(PRIME_CUBE_ROOTS.len() >= MAX_DEPTH && PRIME_CUBE_ROOTS[0].len() * 64 >= MAX_SIZE);
+
+/// Padding rule applied to the last block at finalization
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Padding {
+    /// Append a single `0x80` byte, then zero bytes, then the message length in bits
+    /// as a 64-bit big-endian integer, so that the padded message is a whole number of
+    /// blocks. An extra block is added when the length does not fit. This is
+    /// Merkle–Damgård strengthening: no two messages share a padded form.
+    #[default]
+    Strengthened,
+    /// Fill the last partial block with `'A'` bytes, or hash a full block of `'A'`
+    /// bytes when there is no partial block. The fill of a partial block is the
+    /// original one. The original had no rule for a message ending on a block
+    /// boundary: it processed no further block and returned the digest left over from
+    /// the last partial tail, all zeros for the empty message. The full block of `'A'`
+    /// bytes replaces that. The fill is ambiguous (`"abc"` and `"abcA"` collide) and
+    /// is kept only for compatibility experiments.
+    Legacy,
+}
+
+impl Padding {
+    /// Pad the partial last block `tail` of a `message_len`-byte message into whole blocks
+    pub(crate) fn pad(self, tail: &[u8], message_len: usize, block_size: usize) -> Vec<u8> {
+        let mut padded = tail.to_vec();
+        match self {
+            Padding::Strengthened => {
+                padded.push(0x80);
+                while padded.len() % block_size != block_size - 8 {
+                    padded.push(0);
+                }
+                let bit_length = (message_len as u64).wrapping_mul(8);
+                padded.extend_from_slice(&bit_length.to_be_bytes());
+            }
+            Padding::Legacy => {
+                padded.resize(block_size, b'A');
+            }
+        }
+        padded
+    }
+}
 
-// First primes cubic root representation (truncated for brevity)
-// This is a truncated version of the table - in production code, the full table should be used
-const FIRST_PRIMES_CUBIC_ROOT_DEC_REP: [u64; 64] = [
-    0xa54ddd35b5, 0xd48ef058b4, 0x342640f4c9, 0x51cd2de3e9, 0x8503094982, 0x9b9fd7c452, 0xc47a643e0c, 0xa8602fe35a,
-    0x20eaf18d67, 0x4d59f727fe, 0x685bd4533f, 0x7534dcd163, 0x8dc0dcbb8b, 0xb01624cb6d, 0xcfeabbf181, 0xda0b94f97e,
-    0x8f4d86d1a9, 0x20c96455af, 0x29c172f7dd, 0x43b770ba12, 0x544d18005f, 0x6c34f761a1, 0x8a76ef782f, 0x98f8d17ddc,
-    0xa0151027c6, 0xae080d4b7b, 0xb4e03c992b, 0xc251542f88, 0x3dc28be52f, 0xb75c7e128f, 0x241edeb8f4, 0x04317d07b2,
-    0x46305e3a3d, 0x4bafebecef, 0x09308a3b6b, 0x6bb275e451, 0x76044f4b33, 0x85311d5237, 0x94051aaeb0, 0x98e38ef4df,
-    0xb0b5da348c, 0xb55fd044a0, 0xbe9b372069, 0xc32ceea80e, 0xddf799a193, 0x0eee44484b, 0x17529bf549, 0x1b7b53489d,
-    0x23ba4d74a0, 0x2febef5a50, 0x33f0db9016, 0x47b5d89777, 0x5352304156, 0x5ec09f1622, 0x6a02e0a83b, 0x0af9027c88,
-    0x78c3f873a6, 0x8009496a17, 0x83a5537ad2, 0x95715f4210, 0xadb0de7719, 0xb47bab87d1, 0xb7db7bc375, 0xbe90221e69
-];
+/// How the permutation output of a block becomes the next chaining value
+///
+/// A block is permuted under layer encodings taken from earlier chaining values, so
+/// with `Permutation` anyone who knows the chaining value before a block can invert
+/// its output back to the block (see `BlockPermutation`); in particular every
+/// one-block message can be recovered from its digest. The feed-forward modes XOR
+/// the input back into the output, which removes that inverse.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// The permutation output itself. This is the original construction and stays
+    /// the default for compatibility.
+    #[default]
+    Permutation,
+    /// Matyas–Meyer–Oseas: the permutation output XOR the block
+    MatyasMeyerOseas,
+    /// Miyaguchi–Preneel: the permutation output XOR the block XOR the previous
+    /// chaining value
+    MiyaguchiPreneel,
+}
 
-/// Convert a cubic root to an array of 8 hex digits
-fn cubic_root_array(cr: u64) -> Vec<u8> {
-    let mut ret = vec![0; 8];
-    let mut h = format!("{:x}", cr);
-    while h.len() < 10 {
-        h = format!("0{}", h);
-    }
-    h = h[h.len() - 10..].to_string();
-    for i in 0..8 {
-        if let Some(digit) = h.chars().nth(i) {
-            ret[i] = u8::from_str_radix(&digit.to_string(), 16).unwrap_or(0);
+impl Compression {
+    /// Combine the permutation `output` of `block` with its inputs, given as state words
+    pub(crate) fn feed_forward(self, output: &mut [u64], block: &[u64], chaining: &[u64]) {
+        match self {
+            Compression::Permutation => {}
+            Compression::MatyasMeyerOseas => {
+                for (word, &b) in output.iter_mut().zip(block) {
+                    *word ^= b;
+                }
+            }
+            Compression::MiyaguchiPreneel => {
+                for ((word, &b), &h) in output.iter_mut().zip(block).zip(chaining) {
+                    *word ^= b ^ h;
+                }
+            }
         }
     }
-    ret
 }
 
+/// Where the layer encodings of each block come from
+///
+/// Each schedule carries a version number, so stored digests can record the one
+/// they were made with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum KeySchedule {
+    /// Version 1: layer `l` is the `l`-th most recent chaining value, or the initial
+    /// state for the first `depth` blocks, so the first block always runs the same
+    /// circuit. This is the original schedule and stays the default.
+    #[default]
+    Chained,
+    /// Version 2: the chained encodings, each also XORed with the block and its
+    /// index. Layer `l` gets the block XOR a counter block, which holds the block
+    /// index as a 64-bit big-endian integer in its last 8 bytes, rotated by `l + 1`
+    /// nibbles, so gate selection depends on the data from the first block on. Every
+    /// layer is recompiled for every block, which makes hashing slower, and batched
+    /// hashing runs one message at a time. The counter blocks of `finalize_xof`
+    /// carry no data and keep the chained layers.
+    MessageDependent,
+}
+
+impl KeySchedule {
+    /// Version number of the schedule
+    pub fn version(self) -> u32 {
+        match self {
+            KeySchedule::Chained => 1,
+            KeySchedule::MessageDependent => 2,
+        }
+    }
+}
+
+/// Digest bytes, `output_size()` long
+pub type Digest = Vec<u8>;
+
+#[derive(Clone)]
 pub struct Mirror256 {
-    buffer: String,
+    buffer: Vec<u8>,
     counter: usize,
     depth: usize,
     size: usize,
-    last_hashes: Vec<Vec<u8>>,
-    hashed: Vec<u8>,
+    /// Digest length in bits, below `size` for a wide-pipe hasher
+    output_bits: usize,
+    /// Whether a key replaced the initial state, which sends full-width digests
+    /// through the output transformation too
+    keyed: bool,
+    width: Width,
+    padding: Padding,
+    compression: Compression,
+    key_schedule: KeySchedule,
+    /// Compiled layer encodings, most recent block hash first
+    schedule: GateSchedule,
+    /// Number of blocks hashed so far
+    blocks: u64,
+    /// Layer codes of the current block under `KeySchedule::MessageDependent`
+    block_codes: Vec<LayerCode>,
+}
+
+impl Default for Mirror256 {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Mirror256 {
-    /// Create a new Mirror256 hasher
-    pub fn new(m: Option<&str>, depth: Option<usize>, size: Option<usize>, use_standard_state: bool) -> Self {
-        let depth = depth.unwrap_or(DEFAULT_DEPTH);
-        let size = size.unwrap_or(DEFAULT_SIZE);
+    /// Create a new Mirror256 hasher with the standard parameters
+    pub fn new() -> Self {
+        Self::from_builder(&Mirror256Builder::new())
+    }
+    
+    /// Create a new Mirror256 hasher with the standard parameters that has absorbed `m`
+    pub fn new_from_bytes(m: &[u8]) -> Self {
+        let mut hasher = Self::new();
+        hasher.update(m);
+        hasher
+    }
+    
+    /// Start configuring a hasher with non-standard parameters
+    pub fn builder() -> Mirror256Builder {
+        Mirror256Builder::new()
+    }
+    
+    /// Create a hasher from parameters already validated by the builder
+    pub(crate) fn from_builder(params: &Mirror256Builder) -> Self {
+        if !params.key.0.is_empty() {
+            // The key replaces the initial state; the other parameters went into deriving it
+            // Layer encodings are whole chaining values, also for a wide-pipe hasher
+            let unkeyed = Self::from_builder(&Mirror256Builder { key: Default::default(), output_size: None, ..params.clone() });
+            let initial_value = mac::keyed_initial_value(&unkeyed, &params.key.0);
+            let mut keyed = Self::from_builder(&Mirror256Builder {
+                initial_value: Some(initial_value),
+                personalization: Vec::new(),
+                salt: Vec::new(),
+                key: Default::default(),
+                ..params.clone()
+            });
+            keyed.keyed = true;
+            return keyed;
+        }
         
-        let mut hasher = Mirror256 {
-            buffer: String::new(),
-            counter: 0,
-            depth,
-            size,
-            last_hashes: Vec::new(),
-            hashed: vec![0; size / 4],
+        let Mirror256Builder { depth, size, initial_state, padding, compression, key_schedule, .. } = *params;
+        let width = Width::new(size);
+        let output_bits = params.output_size.unwrap_or(size);
+        let truncated = (output_bits < size).then_some(output_bits);
+        let encodings = || -> Vec<Vec<u64>> {
+            if let Some(encodings) = &params.initial_value {
+                return encodings
+                    .iter()
+                    .map(|encoding| {
+                        let mut words = vec![0; width.words()];
+                        permutation::unpack(width, encoding, &mut words);
+                        words
+                    })
+                    .collect();
+            }
+            
+            // Initialize the state with some non-zero values
+            let nibbles = match initial_state {
+                InitialState::Standard => standard_state(depth, size),
+                InitialState::Seeded(seed) => seeded_state(seed, depth, size),
+            };
+            nibbles.iter().map(|layer| permutation::from_nibbles(width, layer)).collect()
+        };
+        let compile = |encodings: Vec<Vec<u64>>| -> Vec<LayerCode> {
+            encodings.iter().map(|encoding| LayerCode::compile(width, encoding)).collect()
         };
         
-        // Initialize the state with some non-zero values
-        if use_standard_state {
-            hasher.init_standard_state();
+        // Only the built-in states without parameters are shared between hashers
+        let shared = params.initial_value.is_none()
+            && params.personalization.is_empty()
+            && params.salt.is_empty()
+            && truncated.is_none();
+        let initial = if shared {
+            schedule::shared_initial(depth, size, initial_state, || compile(encodings()))
         } else {
-            hasher.init_last_hashes();
-        }
+            let mut encodings = encodings();
+            absorb_parameters(width, &mut encodings, &params.personalization, &params.salt, truncated);
+            compile(encodings).into()
+        };
         
-        if let Some(message) = m {
-            hasher.update(message);
+        Mirror256 {
+            buffer: Vec::with_capacity(size / 8),
+            counter: 0,
+            depth,
+            size,
+            output_bits,
+            keyed: false,
+            width,
+            padding,
+            compression,
+            key_schedule,
+            schedule: GateSchedule::new(initial),
+            blocks: 0,
+            block_codes: Vec::new(),
         }
-        
-        hasher
     }
     
-    /// Initialize standard state using cubic roots of primes
-    fn init_standard_state(&mut self) {
-        // Initialize with non-zero values
-        while self.last_hashes.len() < self.depth {
-            let i = self.last_hashes.len();
-            let mut layer = Vec::new();
-            
-            if i < FIRST_PRIMES_CUBIC_ROOT_DEC_REP.len() {
-                let jprimerep = FIRST_PRIMES_CUBIC_ROOT_DEC_REP[i];
-                layer.extend(cubic_root_array(jprimerep));
-                
-                // Fill the rest of the layer with some non-zero values
-                while layer.len() < self.size / 4 {
-                    // Use i+1 to ensure non-zero values for the test
-                    layer.push(((i + 1) % 16) as u8);
-                }
-                
-                self.last_hashes.push(layer);
-            } else {
-                // If we run out of predefined primes, use a deterministic pattern
-                let mut layer = vec![0; self.size / 4];
-                for j in 0..layer.len() {
-                    layer[j] = ((i + j) % 16) as u8;
-                }
-                self.last_hashes.push(layer);
-            }
-        }
+    /// State size in bits, which is also the width of the gate network
+    pub fn size(&self) -> usize {
+        self.size
     }
     
-    /// Initialize random hashes
-    fn init_last_hashes(&mut self) {
-        let mut rng = StdRng::seed_from_u64(777);
-        while self.last_hashes.len() < self.depth {
-            let mut random_hash = vec![0; self.size / 4];
-            for i in 0..self.size / 4 {
-                random_hash[i] = rng.gen_range(0..16) as u8;
-            }
-            self.last_hashes.push(random_hash);
-        }
+    /// Number of layers of the gate network
+    pub fn depth(&self) -> usize {
+        self.depth
     }
     
-    /// Unpack a string into an array of nibbles
-    fn unpack(&self, m: &str) -> Vec<u8> {
-        let mut ret = vec![0; 64];
-        let bytes = m.as_bytes();
-        
-        let mut i = 0;
-        for &b in bytes.iter().take(32) {
-            // high nibble
-            ret[i] = (b >> 4) & 0x0F;
-            i += 1;
-            // low nibble
-            ret[i] = b & 0x0F;
-            i += 1;
-        }
-        ret
+    /// Message block size in bytes
+    pub fn block_size(&self) -> usize {
+        self.size / 8
     }
     
-    /// Pack an array of nibbles into a byte array
-    fn pack(&self, hm: &[u8]) -> Vec<u8> {
-        let mut hb = vec![0; self.size / 8];
-        for i in 0..self.size / 8 {
-            if i * 2 < hm.len() {
-                let mut b = hm[i * 2] << 4;
-                if i * 2 + 1 < hm.len() {
-                    b |= hm[i * 2 + 1];
-                }
-                hb[i] = b;
-            }
-        }
-        hb
+    /// Digest length in bytes
+    pub fn output_size(&self) -> usize {
+        self.output_bits / 8
     }
     
     /// Update the hasher with new data
-    pub fn update(&mut self, m: &str) {
-        if m.is_empty() {
-            return;
-        }
-        
-        // Add the string to the buffer
-        self.buffer.push_str(m);
+    ///
+    /// Only complete blocks are processed here; the tail waits in the buffer until
+    /// `finalize`, so the digest does not depend on how the input is split across calls.
+    pub fn update(&mut self, m: &[u8]) {
         self.counter += m.len();
+        let mut m = m;
         
-        // Process complete 32-byte chunks
-        while self.buffer.len() >= 32 {
-            // Get the first 32 bytes, not characters
-            let chunk_bytes = self.buffer.as_bytes();
-            let chunk = std::str::from_utf8(&chunk_bytes[..32])
-                .unwrap_or("................................");  // Fallback if not valid UTF-8
-            
-            let hm = self.mirror256_process(chunk);
-            
-            // Update the last hashes
-            self.last_hashes.insert(0, hm.clone());
-            self.last_hashes.truncate(self.depth);
-            
-            // Remove the processed chunk
-            self.buffer = self.buffer[chunk.len()..].to_string();
-        }
-        
-        // Process any remaining data
-        if !self.buffer.is_empty() || m.is_empty() {
-            let mut padded_buffer = self.buffer.clone();
-            let padding_len = 32_usize.saturating_sub(padded_buffer.len());
-            padded_buffer.push_str(&"A".repeat(padding_len));
-            
-            // Ensure we only take exactly 32 bytes
-            let chunk_bytes = padded_buffer.as_bytes();
-            let chunk = std::str::from_utf8(&chunk_bytes[..32])
-                .unwrap_or("................................");
-                
-            let hm = self.mirror256_process(chunk);
-            self.hashed = hm;
+        // Top up a partially filled block first
+        let block_size = self.block_size();
+        if !self.buffer.is_empty() {
+            let take = (block_size - self.buffer.len()).min(m.len());
+            self.buffer.extend_from_slice(&m[..take]);
+            m = &m[take..];
+            if self.buffer.len() < block_size {
+                return;
+            }
+            let block = std::mem::take(&mut self.buffer);
+            self.process_block(&block);
+            self.buffer = block;
+            self.buffer.clear();
         }
-    }
-    
-    /// Process a 32-byte chunk and return the hash
-    fn mirror256_process(&self, m: &str) -> Vec<u8> {
-        let mut block = self.unpack(m);
         
-        // Apply all hash layers
-        for layer in 0..self.depth {
-            block = self.hash_layer_pass(layer, &block);
+        // Process complete blocks straight from the input
+        let mut chunks = m.chunks_exact(block_size);
+        for block in &mut chunks {
+            self.process_block(block);
         }
-        
-        block
+        self.buffer.extend_from_slice(chunks.remainder());
     }
     
-    /// Apply a single hashing layer
-    fn hash_layer_pass(&self, layer: usize, block: &[u8]) -> Vec<u8> {
-        let layer_hash = &self.last_hashes[layer];
-        let mut block = block.to_vec();
-        
-        // First XOR with layer encoding to avoid 0 to 0 hashes
-        for gate_index in 0..self.size / 4 {
-            if gate_index < block.len() && gate_index < layer_hash.len() {
-                block[gate_index] ^= layer_hash[gate_index];
-            }
-        }
-        
-        // First sublayer
-        for gate_index in 0..self.size / 4 {
-            if gate_index < layer_hash.len() {
-                let gate_type = layer_hash[gate_index] & 0x3;
-                
-                let gate_name = gate_type & 1;  // Toffoli or Fredkin
-                let gate_symmetry = gate_type >> 1;  // Regular or Mirrored
-                
-                block = self.apply_gate(gate_index, gate_name, gate_symmetry, &block, true, layer);
-            }
-        }
-        
-        // Second sublayer
-        for gate_index in 0..self.size / 4 {
-            if gate_index < layer_hash.len() {
-                let gate_type = (layer_hash[gate_index] & 0xC) >> 2;
-                
-                let gate_name = gate_type & 1;  // Toffoli or Fredkin
-                let gate_symmetry = gate_type >> 1;  // Regular or Mirrored
-                
-                block = self.apply_gate(gate_index, gate_name, gate_symmetry, &block, false, layer);
-            }
-        }
-        
-        block
+    /// Update the hasher with a string, hashing its UTF-8 bytes
+    pub fn update_str(&mut self, m: &str) {
+        self.update(m.as_bytes());
     }
     
-    /// Get the wire index for a gate
-    fn get_wire(&self, gate_index: usize, first_sublayer: bool, offset: usize) -> usize {
-        (gate_index * 4 + offset + (if !first_sublayer { 2 } else { 0 })) % self.size
+    /// Pad the buffered tail, process the final block(s) and return the digest bytes
+    pub fn finalize(mut self) -> Vec<u8> {
+        self.absorb_padding();
+        self.output(&self.schedule)
     }
     
-    /// Get the bit value at a specific wire
-    fn get_bit(&self, block: &[u8], wire: usize) -> u8 {
-        if wire / 4 < block.len() {
-            (block[wire / 4] >> (wire % 4)) & 1
-        } else {
-            0
-        }
+    /// Pad and process the buffered tail, then return a reader of unlimited output
+    ///
+    /// Any prefix of the output is the output of that length; see `XofReader` for how
+    /// the stream is produced. The output transformation of a wide-pipe hasher is not
+    /// applied, though its digest length is part of the initial state.
+    pub fn finalize_xof(mut self) -> XofReader {
+        self.absorb_padding();
+        XofReader::new(self.width, self.schedule)
     }
     
-    /// Set the bit value at a specific wire
-    fn set_bit(block: &mut [u8], wire: usize, bit: u8) {
-        if wire / 4 < block.len() {
-            let old_nib = block[wire / 4];
-            let ret = (old_nib & (15 ^ (1 << (wire % 4)))) | (bit << (wire % 4));
-            block[wire / 4] = ret;
+    /// Process the padded tail, leaving the digest as the layer 0 encoding
+    fn absorb_padding(&mut self) {
+        let padded = self.padded_tail();
+        for block in padded.chunks(self.block_size()) {
+            self.process_block(block);
         }
     }
     
-    /// Apply a gate (Toffoli or Fredkin) to the block
-    fn apply_gate(&self, gate_index: usize, gate_name: u8, gate_symmetry: u8, block: &[u8], first_sublayer: bool, layer: usize) -> Vec<u8> {
-        let initial_offset = layer % 2;
-        let wire1 = self.get_wire(gate_index, first_sublayer, initial_offset + 0);
-        let wire2 = self.get_wire(gate_index, first_sublayer, initial_offset + 1);
-        let wire3 = self.get_wire(gate_index, first_sublayer, initial_offset + 2);
-        
-        let val1 = self.get_bit(block, wire1);
-        let val2 = self.get_bit(block, wire2);
-        let val3 = self.get_bit(block, wire3);
-        
-        let mut oval1 = val1;
-        let mut oval2 = val2;
-        let mut oval3 = val3;
-        
-        // Toffoli and Regular
-        if gate_name == TOFFOLI && gate_symmetry == REGULAR && (val1 == 1 && val2 == 1) {
-            oval3 = val3 ^ (val1 & val2);
+    /// The digest, given the schedule after the last padded block
+    ///
+    /// At full width the digest of an unkeyed hasher is the last chaining value. A
+    /// shorter or keyed digest comes from the output transformation: the last chaining value XOR a block holding the digest
+    /// length in bits as a 64-bit big-endian integer in its first 8 bytes, permuted under
+    /// the final layers in as many passes as it takes to run `2 * size` layers, XORed
+    /// with its input and truncated. One pass would not do, since it leaves most digest bits
+    /// unchanged (see the `block` module); larger states need proportionally more layers.
+    pub(crate) fn output(&self, schedule: &GateSchedule) -> Digest {
+        let chaining = schedule.latest().encoding();
+        if self.output_bits == self.size && !self.keyed {
+            return permutation::pack(self.width, chaining);
         }
-        // Toffoli and Mirrored
-        else if gate_name == TOFFOLI && gate_symmetry == MIRRORED && (val2 == 1 && val3 == 1) {
-            oval1 = val1 ^ (val2 & val3);
-        }
-        // Fredkin and Regular
-        else if gate_name == FREDKIN && gate_symmetry == REGULAR && val1 == 1 && val2 != val3 {
-            oval2 = val3;
-            oval3 = val2;
+
+        let mut block = vec![0; self.block_size()];
+        block[..8].copy_from_slice(&(self.output_bits as u64).to_be_bytes());
+        let mut state = [0u64; MAX_WORDS];
+        permutation::unpack(self.width, &block, &mut state);
+        for (word, &c) in state.iter_mut().zip(chaining) {
+            *word ^= c;
         }
-        // Fredkin and Mirrored
-        else if gate_name == FREDKIN && gate_symmetry == MIRRORED && val3 == 1 && val1 != val2 {
-            oval1 = val2;
-            oval2 = val1;
+        let input = state;
+        for _ in 0..(2 * self.size).div_ceil(self.depth) {
+            permutation::permute(self.width, &mut state, schedule.layers());
         }
+        Compression::MatyasMeyerOseas.feed_forward(&mut state, &input, chaining);
+
+        let mut digest = permutation::pack(self.width, &state);
+        digest.truncate(self.output_size());
+        digest
+    }
+    
+    /// Apply the padding rule to the buffered tail
+    fn padded_tail(&self) -> Vec<u8> {
+        self.padding.pad(&self.buffer, self.counter, self.block_size())
+    }
+    
+    /// Hash a full block and make its hash the most recent layer encoding
+    fn process_block(&mut self, block: &[u8]) {
+        let mut state = [0u64; MAX_WORDS];
+        permutation::unpack(self.width, block, &mut state);
+        let input = state;
         
-        let mut result = block.to_vec();
-        
-        if val1 != oval1 {
-            Self::set_bit(&mut result, wire1, oval1);
-        }
-        if val2 != oval2 {
-            Self::set_bit(&mut result, wire2, oval2);
-        }
-        if val3 != oval3 {
-            Self::set_bit(&mut result, wire3, oval3);
+        // Apply all hash layers
+        match self.key_schedule {
+            KeySchedule::Chained => permutation::permute(self.width, &mut state, self.schedule.layers()),
+            KeySchedule::MessageDependent => {
+                schedule::message_dependent(self.width, &self.schedule, &input, self.blocks, &mut self.block_codes);
+                permutation::permute(self.width, &mut state, &self.block_codes);
+            }
         }
+        self.blocks += 1;
         
-        result
+        self.compression.feed_forward(&mut state, &input, self.schedule.latest().encoding());
+        self.schedule.push(self.width, &state);
+    }
+    
+    /// The permutation the next complete block goes through
+    ///
+    /// Its output on a block, combined with the block as set by the hasher's
+    /// `Compression`, becomes the layer 0 encoding of the following block, or the
+    /// digest if that block is the last one. Under `KeySchedule::MessageDependent` the
+    /// layers also depend on the block, so this covers only their chained part.
+    pub fn block_permutation(&self) -> BlockPermutation {
+        BlockPermutation::from_schedule(self.width, &self.schedule)
+    }
+    
+    /// Digests of many independent messages, each hashed as if appended to a copy of
+    /// this hasher; a fresh hasher gives their plain digests
+    ///
+    /// Blocks of several messages are evaluated together across SIMD lanes, using
+    /// AVX2 or SSE2 when the CPU supports them.
+    pub fn hash_many(&self, messages: &[&[u8]]) -> Vec<Digest> {
+        multi::hash_many(self, messages)
+    }
+    
+    /// `hash_many` with the messages spread over the rayon thread pool; the digests
+    /// are the same as those of `hash_many`
+    #[cfg(feature = "parallel")]
+    pub fn par_hash_many(&self, messages: &[&[u8]]) -> Vec<Digest> {
+        parallel::hash_many(self, messages)
     }
     
-    /// Get the digest as a byte array
+    /// Get the digest of the data absorbed so far as a byte array
     pub fn digest(&self) -> Vec<u8> {
-        self.pack(&self.hashed)
+        self.clone().finalize()
     }
     
     /// Get the digest as a hexadecimal string
@@ -329,9 +460,87 @@ impl Mirror256 {
     }
 }
 
-/// Create a new Mirror256 hasher
+/// Absorb the personalization string, the salt and a truncated digest length into
+/// the layer encodings
+///
+/// Each is zero-padded to a one-block parameter block whose nibbles are XORed into
+/// encodings: the personalization into every layer, so every gate layer of every
+/// block changes, and the salt into the odd layers, so that the even layers tell
+/// the personalization apart and the odd layers then tell the salt apart. A digest
+/// length below the state size goes into every layer as a 64-bit big-endian integer
+/// in the last 8 bytes of its block, so truncated digests of different lengths come
+/// from different functions rather than from one digest cut short. The builder keeps
+/// the personalization out of those bytes, so the two cannot cancel.
+fn absorb_parameters(
+    width: Width,
+    encodings: &mut [Vec<u64>],
+    personalization: &[u8],
+    salt: &[u8],
+    truncated: Option<usize>,
+) {
+    let mut personal_block = [0u64; MAX_WORDS];
+    permutation::unpack(width, personalization, &mut personal_block);
+    let mut salt_block = [0u64; MAX_WORDS];
+    permutation::unpack(width, salt, &mut salt_block);
+    let mut length_block = [0u64; MAX_WORDS];
+    if let Some(bits) = truncated {
+        let mut block = vec![0; width.bits() / 8];
+        let offset = block.len() - 8;
+        block[offset..].copy_from_slice(&(bits as u64).to_be_bytes());
+        permutation::unpack(width, &block, &mut length_block);
+    }
+    
+    for (layer, encoding) in encodings.iter_mut().enumerate() {
+        for (k, word) in encoding.iter_mut().enumerate() {
+            *word ^= personal_block[k] ^ length_block[k];
+            if layer % 2 == 1 {
+                *word ^= salt_block[k];
+            }
+        }
+    }
+}
+
+/// Standard layer encodings as nibbles
+///
+/// Layer `i` holds the first `size / 4` hexadecimal digits of the fractional part of
+/// the cube root of the `i`-th prime, most significant digit first, so that layer 0
+/// starts with the digits of `cbrt(2) = 1.428a2f98d7...` (hex).
+fn standard_state(depth: usize, size: usize) -> Vec<Vec<u8>> {
+    PRIME_CUBE_ROOTS[..depth]
+        .iter()
+        .map(|root| (0..size / 4).map(|n| ((root[n / 16] >> (60 - 4 * (n % 16))) & 0xF) as u8).collect())
+        .collect()
+}
+
+/// Pseudo-random layer encodings as nibbles, one SplitMix64 output per 16 nibbles
+fn seeded_state(seed: u64, depth: usize, size: usize) -> Vec<Vec<u8>> {
+    let mut rng = SplitMix64::new(seed);
+    (0..depth)
+        .map(|_| {
+            let words: Vec<u64> = (0..size.div_ceil(64)).map(|_| rng.next_u64()).collect();
+            (0..size / 4).map(|n| ((words[n / 16] >> (4 * (n % 16))) & 0xF) as u8).collect()
+        })
+        .collect()
+}
+
+/// Digests of many independent messages with the standard parameters
+pub fn hash_many(messages: &[&[u8]]) -> Vec<Digest> {
+    Mirror256::new().hash_many(messages)
+}
+
+/// Digests of many independent messages with the standard parameters, on the thread pool
+#[cfg(feature = "parallel")]
+pub fn par_hash_many(messages: &[&[u8]]) -> Vec<Digest> {
+    Mirror256::new().par_hash_many(messages)
+}
+
+/// Create a new Mirror256 hasher with the standard parameters, optionally absorbing a message
 pub fn new(m: Option<&str>) -> Mirror256 {
-    Mirror256::new(m, None, None, true)
+    let mut hasher = Mirror256::new();
+    if let Some(message) = m {
+        hasher.update_str(message);
+    }
+    hasher
 }
 
 #[cfg(test)]
@@ -340,31 +549,81 @@ mod tests {
     
     #[test]
     fn test_empty_string() {
-        let hasher = Mirror256::new(Some(""), None, None, true);
+        let hasher = new(Some(""));
         let digest = hasher.hexdigest();
         assert!(!digest.is_empty());
     }
     
     #[test]
     fn test_canary_string() {
-        let hasher = Mirror256::new(Some("This is the canary."), None, None, true);
+        let hasher = new(Some("This is the canary."));
         let digest = hasher.hexdigest();
         assert!(!digest.is_empty());
     }
     
     #[test]
     fn test_consistency() {
-        let hasher1 = Mirror256::new(Some("test"), None, None, true);
-        let hasher2 = Mirror256::new(Some("test"), None, None, true);
+        let hasher1 = new(Some("test"));
+        let hasher2 = new(Some("test"));
         
         assert_eq!(hasher1.hexdigest(), hasher2.hexdigest());
     }
     
     #[test]
     fn test_different_inputs() {
-        let hasher1 = Mirror256::new(Some("test1"), None, None, true);
-        let hasher2 = Mirror256::new(Some("test2"), None, None, true);
+        let hasher1 = new(Some("test1"));
+        let hasher2 = new(Some("test2"));
         
         assert_ne!(hasher1.hexdigest(), hasher2.hexdigest());
     }
-} 
\ No newline at end of file
+    
+    /// Hash a message with the nibble reference network and the same chaining and padding
+    fn reference_digest(depth: usize, size: usize, m: &[u8]) -> Vec<u8> {
+        let mut hasher = Mirror256::builder().depth(depth).size(size).build().unwrap();
+        hasher.update(m);
+        let mut padded = m[..m.len() - hasher.buffer.len()].to_vec();
+        padded.extend_from_slice(&hasher.padded_tail());
+        
+        let mut encodings = standard_state(depth, size);
+        for block in padded.chunks(size / 8) {
+            let hm = reference::mirror256_process(size, &encodings, block);
+            encodings.insert(0, hm);
+            encodings.truncate(depth);
+        }
+        reference::pack(size, &encodings[0])
+    }
+    
+    #[test]
+    fn test_bit_sliced_digest_matches_reference() {
+        for (depth, size) in [(16, 128), (8, 224), (32, 256), (4, 512)] {
+            for len in [0, 3, size / 8, 100] {
+                let m: Vec<u8> = (0..len).map(|i| (i * 7 + 1) as u8).collect();
+                let mut hasher = Mirror256::builder().depth(depth).size(size).build().unwrap();
+                hasher.update(&m);
+                assert_eq!(hasher.digest(), reference_digest(depth, size, &m), "depth {} size {} len {}", depth, size, len);
+            }
+        }
+    }
+    
+    #[test]
+    fn test_cube_roots_match_sha512_constants() {
+        // SHA-512 round constants are the first 64 fractional bits of the same roots
+        let sha512 = [(0, 0x428a2f98d728ae22), (1, 0x7137449123ef65cd), (2, 0xb5c0fbcfec4d3b2f), (79, 0x6c44198c4a475817)];
+        for (i, constant) in sha512 {
+            assert_eq!(PRIME_CUBE_ROOTS[i][0], constant, "prime {}", i);
+        }
+        assert_eq!(standard_state(1, 32)[0], [4, 2, 8, 0xa, 2, 0xf, 9, 8]);
+    }
+    
+    #[test]
+    fn test_cube_roots_are_exact() {
+        // r = floor(cbrt(p) * 2^36) satisfies r^3 <= p * 2^108 < (r + 1)^3
+        let mut primes = (2u128..).filter(|&n| (2..n).take_while(|d| d * d <= n).all(|d| n % d != 0));
+        for root in PRIME_CUBE_ROOTS.iter() {
+            let p = primes.next().unwrap();
+            let integer = (1u128..).find(|i| i * i * i > p).unwrap() - 1;
+            let r = (integer << 36) | u128::from(root[0] >> 28);
+            assert!(r * r * r <= p << 108 && (r + 1) * (r + 1) * (r + 1) > p << 108, "prime {}", p);
+        }
+    }
+}
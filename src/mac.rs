@@ -0,0 +1,135 @@
+//! Message authentication
+//!
+//! `HmacMirror256` is HMAC (RFC 2104) over a Mirror256 hasher: with the key `K`
+//! padded to one block, the tag of `m` is `H((K ^ opad) ‖ H((K ^ ipad) ‖ m))`.
+//! The native keyed mode set with `Mirror256Builder::key` needs one pass instead of
+//! two; see `keyed_initial_value`.
+
+use crate::{Digest, Mirror256, Mirror256Error};
+
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5c;
+
+/// Prefix of the messages that derive keyed layer encodings
+const KEYED_LAYER_DOMAIN: &[u8] = b"Mirror256 keyed layer";
+
+/// HMAC over Mirror256, or over any configured hasher
+///
+/// ```
+/// use mirror_hash::HmacMirror256;
+///
+/// let mut mac = HmacMirror256::new_from_slice(b"secret key").unwrap();
+/// mac.update(b"message");
+/// let tag = mac.clone().finalize();
+/// assert!(mac.verify(&tag).is_ok());
+/// ```
+#[derive(Clone)]
+pub struct HmacMirror256 {
+    inner: Mirror256,
+    outer: Mirror256,
+}
+
+impl HmacMirror256 {
+    /// HMAC with the standard Mirror256 parameters
+    ///
+    /// Keys of any length are accepted, so this does not fail at present; the
+    /// `Result` leaves room for key checks.
+    pub fn new_from_slice(key: &[u8]) -> Result<Self, Mirror256Error> {
+        Self::with_hasher(&Mirror256::new(), key)
+    }
+
+    /// HMAC over copies of `hasher`, whose block size sets the key block
+    pub fn with_hasher(hasher: &Mirror256, key: &[u8]) -> Result<Self, Mirror256Error> {
+        let block_size = hasher.block_size();
+
+        // Keys longer than a block are hashed first
+        let mut key_block = if key.len() > block_size {
+            let mut hashed = hasher.clone();
+            hashed.update(key);
+            hashed.finalize()
+        } else {
+            key.to_vec()
+        };
+        key_block.resize(block_size, 0);
+
+        let pad = |byte: u8| -> Mirror256 {
+            let mut h = hasher.clone();
+            h.update(&key_block.iter().map(|k| k ^ byte).collect::<Vec<u8>>());
+            h
+        };
+        Ok(HmacMirror256 { inner: pad(IPAD), outer: pad(OPAD) })
+    }
+
+    /// Absorb more of the message
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    /// Consume the MAC and return the tag
+    pub fn finalize(self) -> Digest {
+        let mut outer = self.outer;
+        outer.update(&self.inner.finalize());
+        outer.finalize()
+    }
+
+    /// Consume the MAC and check `tag` against it in constant time
+    pub fn verify(self, tag: &[u8]) -> Result<(), Mirror256Error> {
+        if constant_time_eq(&self.finalize(), tag) {
+            Ok(())
+        } else {
+            Err(Mirror256Error::MacMismatch)
+        }
+    }
+}
+
+/// Compare two byte strings without branching on their contents; only the
+/// lengths, which are public, can end the comparison early
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let difference = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    std::hint::black_box(difference) == 0
+}
+
+/// Initial layer encodings of the native keyed mode
+///
+/// Layer `i` is the digest, under the unkeyed parameters, of the domain string
+/// `"Mirror256 keyed layer"`, `i` as a 32-bit big-endian integer, the key and layer
+/// `i - 1` (nothing for layer 0), so every layer depends on the whole key and no
+/// keyed hasher starts like an unkeyed one; see `derive_layers`.
+pub(crate) fn keyed_initial_value(unkeyed: &Mirror256, key: &[u8]) -> Vec<Digest> {
+    derive_layers(unkeyed, KEYED_LAYER_DOMAIN, key, unkeyed.depth())
+}
+
+/// `layers` digests under `hasher`, number `i` of `domain`, `i` as a 32-bit big-endian
+/// integer, `key` and digest `i - 1` (nothing for digest 0)
+///
+/// Chaining in the previous digest keeps neighbouring layers apart: digests of
+/// messages that differ in a few bytes share most of their bits, and near-equal
+/// layers largely undo each other since every gate is its own inverse.
+pub(crate) fn derive_layers(hasher: &Mirror256, domain: &[u8], key: &[u8], layers: usize) -> Vec<Digest> {
+    let mut digests: Vec<Digest> = Vec::with_capacity(layers);
+    for layer in 0..layers as u32 {
+        let mut hasher = hasher.clone();
+        hasher.update(domain);
+        hasher.update(&layer.to_be_bytes());
+        hasher.update(key);
+        hasher.update(digests.last().map_or(&[][..], Vec::as_slice));
+        digests.push(hasher.finalize());
+    }
+    digests
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"", b""));
+        assert!(constant_time_eq(b"tag", b"tag"));
+        assert!(!constant_time_eq(b"tag", b"tah"));
+        assert!(!constant_time_eq(b"tag", b"ta"));
+    }
+}
@@ -13,7 +13,7 @@ fn main() {
     println!("Message: {}", message);
     
     // Create a new hasher with the message
-    let hasher = Mirror256::new(Some(&message), None, None, true);
+    let hasher = Mirror256::new_from_bytes(message.as_bytes());
     
     // Get and print the digest
     println!("Hash: {}", hasher.hexdigest());
@@ -30,7 +30,7 @@ fn benchmark() {
     
     loop {
         let random_string = random_alphanumeric_string(32);
-        let hasher = Mirror256::new(Some(&random_string), None, None, true);
+        let hasher = Mirror256::new_from_bytes(random_string.as_bytes());
         
         let _digest = hasher.hexdigest();
         count += 1;
@@ -44,13 +44,13 @@ fn benchmark() {
     
     // Example with a specific message
     let example_message = "This is the canary #42. asdfasdfasdfasdfasdfqwerqwerqwerdfnnjkdfnjldljknsvv";
-    let example_hasher = Mirror256::new(Some(example_message), None, None, true);
+    let example_hasher = Mirror256::new_from_bytes(example_message.as_bytes());
     println!("Example message: {}", example_message);
     println!("Example digest: {}", example_hasher.hexdigest());
     
     // Example with a random string
     let random_message = random_alphanumeric_string(32);
-    let random_hasher = Mirror256::new(Some(&random_message), None, None, true);
+    let random_hasher = Mirror256::new_from_bytes(random_message.as_bytes());
     println!("Example message: {}", random_message);
     println!("Example digest: {}", random_hasher.hexdigest());
 }
@@ -0,0 +1,302 @@
+//! Multi-buffer hashing of independent messages
+//!
+//! Messages are assigned to lanes; each step hashes the next block of every busy
+//! lane with one pass of the gate network over lane words, and a lane whose message
+//! is finished picks up the next pending message. Lanes keep their own gate
+//! schedules, so they may be at different blocks of messages of different lengths.
+
+use crate::permutation::{self, Width, MAX_WORDS};
+use crate::schedule::GateSchedule;
+#[cfg(target_arch = "x86_64")]
+use crate::simd::{Avx2, Sse2};
+use crate::simd::{LaneWord, Portable, MAX_LANES};
+use crate::{Digest, KeySchedule, Mirror256};
+use std::borrow::Cow;
+
+/// Lane word implementation, chosen from the features of the running CPU
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Kernel {
+    Portable,
+    #[cfg(target_arch = "x86_64")]
+    Sse2,
+    #[cfg(target_arch = "x86_64")]
+    Avx2,
+}
+
+impl Kernel {
+    /// The widest kernel the running CPU supports
+    pub(crate) fn detect() -> Self {
+        Self::available().pop().unwrap_or(Kernel::Portable)
+    }
+
+    /// Every kernel the running CPU supports, widest last
+    pub(crate) fn available() -> Vec<Self> {
+        #[allow(unused_mut)]
+        let mut kernels = vec![Kernel::Portable];
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("sse2") {
+                kernels.push(Kernel::Sse2);
+            }
+            if is_x86_feature_detected!("avx2") {
+                kernels.push(Kernel::Avx2);
+            }
+        }
+        kernels
+    }
+
+    /// Number of messages hashed together
+    fn lanes(self) -> usize {
+        match self {
+            Kernel::Portable => Portable::LANES,
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Sse2 => Sse2::LANES,
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Avx2 => Avx2::LANES,
+        }
+    }
+
+    /// Run each state through the layers of the schedule at the same index
+    fn permute(self, width: Width, states: &mut [[u64; MAX_WORDS]], schedules: &[&GateSchedule]) {
+        match self {
+            // SAFETY: portable lane words need no CPU features
+            Kernel::Portable => unsafe { permute_lanes::<Portable>(width, states, schedules) },
+            // SAFETY: these kernels are only available after their features were detected
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Sse2 => unsafe { permute_lanes_sse2(width, states, schedules) },
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Avx2 => unsafe { permute_lanes_avx2(width, states, schedules) },
+        }
+    }
+}
+
+/// # Safety
+///
+/// The CPU must support SSE2.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn permute_lanes_sse2(width: Width, states: &mut [[u64; MAX_WORDS]], schedules: &[&GateSchedule]) {
+    permute_lanes::<Sse2>(width, states, schedules)
+}
+
+/// # Safety
+///
+/// The CPU must support AVX2.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn permute_lanes_avx2(width: Width, states: &mut [[u64; MAX_WORDS]], schedules: &[&GateSchedule]) {
+    permute_lanes::<Avx2>(width, states, schedules)
+}
+
+/// Evaluate up to `V::LANES` states at once; spare lanes repeat the last state
+///
+/// # Safety
+///
+/// The CPU must support the instructions of `V`.
+#[inline(always)]
+unsafe fn permute_lanes<V: LaneWord>(width: Width, states: &mut [[u64; MAX_WORDS]], schedules: &[&GateSchedule]) {
+    let w = width.words();
+    let n = states.len();
+    let lane = |i: usize| i.min(n - 1);
+
+    let mut values = [0u64; MAX_LANES];
+    let mut state = [V::splat(0); MAX_WORDS];
+    for (k, word) in state.iter_mut().enumerate().take(w) {
+        for (i, value) in values.iter_mut().enumerate().take(V::LANES) {
+            *value = states[lane(i)][k];
+        }
+        *word = V::load(&values);
+    }
+
+    let mut code = [V::splat(0); 9 * MAX_WORDS];
+    for layer in 0..schedules[0].depth() {
+        let codes: [&[u64]; MAX_LANES] = std::array::from_fn(|i| schedules[lane(i)].layer(layer).words());
+
+        // Lanes still in the initial state share their layer codes
+        if codes[1..V::LANES].iter().all(|c| std::ptr::eq(c.as_ptr(), codes[0].as_ptr())) {
+            for (word, &value) in code.iter_mut().zip(codes[0]) {
+                *word = V::splat(value);
+            }
+        } else {
+            for (j, word) in code.iter_mut().enumerate().take(9 * w) {
+                for (i, value) in values.iter_mut().enumerate().take(V::LANES) {
+                    *value = codes[i][j];
+                }
+                *word = V::load(&values);
+            }
+        }
+        permutation::apply_code(width, &mut state, &code[..9 * w], layer);
+    }
+
+    for (k, word) in state.iter().enumerate().take(w) {
+        word.store(&mut values);
+        for (i, lane_state) in states.iter_mut().enumerate() {
+            lane_state[k] = values[i];
+        }
+    }
+}
+
+/// A message being hashed in a lane
+struct Job<'a> {
+    index: usize,
+    data: Cow<'a, [u8]>,
+    full_blocks: usize,
+    padded_tail: Vec<u8>,
+    next_block: usize,
+    schedule: GateSchedule,
+}
+
+impl<'a> Job<'a> {
+    /// Continue `hasher` with message number `index`
+    fn new(hasher: &Mirror256, index: usize, message: &'a [u8]) -> Self {
+        let block_size = hasher.block_size();
+        let data = if hasher.buffer.is_empty() {
+            Cow::Borrowed(message)
+        } else {
+            Cow::Owned([hasher.buffer.as_slice(), message].concat())
+        };
+        let full_blocks = data.len() / block_size;
+        let padded_tail = hasher.padding.pad(&data[full_blocks * block_size..], hasher.counter + message.len(), block_size);
+
+        Job {
+            index,
+            data,
+            full_blocks,
+            padded_tail,
+            next_block: 0,
+            schedule: hasher.schedule.clone(),
+        }
+    }
+
+    /// The next block to hash, from the message or from its padded tail
+    fn block(&self, block_size: usize) -> &[u8] {
+        let (source, i) = match self.next_block.checked_sub(self.full_blocks) {
+            None => (self.data.as_ref(), self.next_block),
+            Some(i) => (self.padded_tail.as_slice(), i),
+        };
+        &source[i * block_size..(i + 1) * block_size]
+    }
+
+    /// Whether the last padded block has been hashed
+    fn is_done(&self, block_size: usize) -> bool {
+        self.next_block == self.full_blocks + self.padded_tail.len() / block_size
+    }
+}
+
+/// Digests of `messages`, each appended to a copy of `hasher`
+pub(crate) fn hash_many(hasher: &Mirror256, messages: &[&[u8]]) -> Vec<Digest> {
+    hash_many_with(Kernel::detect(), hasher, messages)
+}
+
+/// `hash_many` with a given kernel
+pub(crate) fn hash_many_with(kernel: Kernel, hasher: &Mirror256, messages: &[&[u8]]) -> Vec<Digest> {
+    // Lanes share compiled layers, which a message-dependent schedule rebuilds per block
+    if hasher.key_schedule != KeySchedule::Chained {
+        return messages
+            .iter()
+            .map(|message| {
+                let mut single = hasher.clone();
+                single.update(message);
+                single.finalize()
+            })
+            .collect();
+    }
+
+    let width = hasher.width;
+    let block_size = hasher.block_size();
+    let mut digests = vec![Digest::new(); messages.len()];
+    let mut pending = messages.iter().enumerate();
+    let mut jobs: Vec<Job> = Vec::with_capacity(kernel.lanes());
+
+    loop {
+        // Idle lanes pick up pending messages
+        while jobs.len() < kernel.lanes() {
+            match pending.next() {
+                Some((index, message)) => jobs.push(Job::new(hasher, index, message)),
+                None => break,
+            }
+        }
+        if jobs.is_empty() {
+            return digests;
+        }
+
+        let n = jobs.len();
+        let mut states = [[0u64; MAX_WORDS]; MAX_LANES];
+        for (state, job) in states.iter_mut().zip(&jobs) {
+            permutation::unpack(width, job.block(block_size), state);
+        }
+        let inputs = states;
+        let schedules: [&GateSchedule; MAX_LANES] = std::array::from_fn(|i| &jobs[i.min(n - 1)].schedule);
+        kernel.permute(width, &mut states[..n], &schedules[..n]);
+
+        for ((job, state), input) in jobs.iter_mut().zip(&mut states).zip(&inputs) {
+            hasher.compression.feed_forward(state, input, job.schedule.latest().encoding());
+            job.schedule.push(width, state);
+            job.next_block += 1;
+        }
+
+        // Finished lanes hand in their digest
+        let mut i = 0;
+        while i < jobs.len() {
+            if jobs[i].is_done(block_size) {
+                let job = jobs.swap_remove(i);
+                digests[job.index] = hasher.output(&job.schedule);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Compression, Mirror256Builder};
+
+    fn messages() -> Vec<Vec<u8>> {
+        (0..23).map(|i| (0..i * 7).map(|j| (i * 31 + j) as u8).collect()).collect()
+    }
+
+    #[test]
+    fn test_every_kernel_matches_single_message_hashing() {
+        let messages = messages();
+        let slices: Vec<&[u8]> = messages.iter().map(Vec::as_slice).collect();
+
+        let configurations = [
+            (16, 128, Compression::Permutation),
+            (12, 224, Compression::MiyaguchiPreneel),
+            (128, 256, Compression::Permutation),
+            (8, 1024, Compression::MatyasMeyerOseas),
+        ];
+        for (depth, size, compression) in configurations {
+            let hasher = Mirror256Builder::new().depth(depth).size(size).compression(compression).build().unwrap();
+            let expected: Vec<Digest> = slices
+                .iter()
+                .map(|m| {
+                    let mut single = hasher.clone();
+                    single.update(m);
+                    single.finalize()
+                })
+                .collect();
+
+            for kernel in Kernel::available() {
+                assert_eq!(hash_many_with(kernel, &hasher, &slices), expected, "{:?} depth {} size {}", kernel, depth, size);
+            }
+        }
+    }
+
+    #[test]
+    fn test_continues_from_absorbed_prefix() {
+        let mut prefix = Mirror256Builder::new().depth(16).build().unwrap();
+        prefix.update(&[7; 45]);
+
+        for kernel in Kernel::available() {
+            let digests = hash_many_with(kernel, &prefix, &[b"", b"suffix", &[1; 40]]);
+            for (digest, suffix) in digests.iter().zip([&b""[..], b"suffix", &[1; 40]]) {
+                let mut single = prefix.clone();
+                single.update(suffix);
+                assert_eq!(digest, &single.finalize());
+            }
+        }
+    }
+}
@@ -0,0 +1,21 @@
+//! Spreading independent messages over the rayon thread pool
+//!
+//! Messages are split into batches, each batch is hashed with the lane-interleaved
+//! driver on one thread, and the digests are collected in message order, so the
+//! result is the same as hashing on a single thread.
+
+use crate::{multi, Digest, Mirror256};
+use rayon::prelude::*;
+
+/// Most messages per task; enough to keep the widest lanes busy
+const MAX_BATCH: usize = 16;
+
+/// Digests of `messages`, each appended to a copy of `hasher`
+pub(crate) fn hash_many(hasher: &Mirror256, messages: &[&[u8]]) -> Vec<Digest> {
+    // Small inputs are split finely so that every thread gets work
+    let batch = messages.len().div_ceil(rayon::current_num_threads()).clamp(1, MAX_BATCH);
+    messages
+        .par_chunks(batch)
+        .flat_map_iter(|batch| multi::hash_many(hasher, batch))
+        .collect()
+}
@@ -0,0 +1,295 @@
+//! Memory-hard password hashing
+//!
+//! Passwords are hashed with ROMix, the mixing function of scrypt, over Mirror256
+//! digests. The password, salt and parameters are hashed into a 32-byte value `X`;
+//! the first loop fills `m` memory blocks with successive hashes of `X`, and each of
+//! the `t` passes of the second loop visits `m` blocks at data-dependent indices,
+//! setting `X = H(X ^ V[j])` with `j` taken from the first 8 bytes of `X`. Computing
+//! the result with less memory costs proportionally more hashing. `H` is Mirror256
+//! with `d` layers and Miyaguchi–Preneel compression, so no step can be run backwards.
+//!
+//! Results are stored as PHC strings,
+//! `$mirror256$d=<layers>,m=<blocks>,t=<passes>$<salt>$<hash>`, with the salt and
+//! hash in unpadded standard base64.
+//!
+//! ```
+//! use mirror_hash::password::{hash_password, verify_password, PasswordParams};
+//!
+//! let params = PasswordParams::new(16, 256, 1).unwrap();
+//! let stored = hash_password(b"correct horse", b"per-user salt", &params).unwrap();
+//! assert!(stored.starts_with("$mirror256$d=16,m=256,t=1$"));
+//! assert!(verify_password(b"correct horse", &stored, &params).is_ok());
+//! assert!(verify_password(b"battery staple", &stored, &params).is_err());
+//! ```
+
+use crate::builder::check_depth;
+use crate::mac::constant_time_eq;
+use crate::{Compression, Digest, Mirror256, Mirror256Error, DEFAULT_DEPTH};
+use std::fmt;
+
+/// Identifier of the algorithm in PHC strings
+const ALGORITHM: &str = "mirror256";
+
+/// Prefix of the initial hash, separating it from other uses of Mirror256
+const PASSWORD_DOMAIN: &[u8] = b"Mirror256 password";
+
+/// Shortest accepted salt in bytes; 16 random bytes are recommended
+pub const MIN_SALT_BYTES: usize = 8;
+
+/// Longest accepted salt in bytes
+pub const MAX_SALT_BYTES: usize = 64;
+
+/// Bytes of a memory block, one digest
+const BLOCK_BYTES: usize = 32;
+
+/// Largest memory cost in blocks; the blocks sit in one allocation of 512 MiB
+pub const MAX_MEMORY_BLOCKS: usize = 1 << 24;
+
+/// Largest number of passes
+pub const MAX_PASSES: usize = 1 << 16;
+
+/// Cost parameters of the password hash
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PasswordParams {
+    depth: usize,
+    memory: usize,
+    passes: usize,
+}
+
+impl Default for PasswordParams {
+    /// 128 layers, 4096 blocks (128 KiB) and 3 passes
+    fn default() -> Self {
+        PasswordParams { depth: DEFAULT_DEPTH, memory: 4096, passes: 3 }
+    }
+}
+
+impl PasswordParams {
+    /// Validate cost parameters: layers of the hash, memory blocks and mixing passes
+    pub fn new(depth: usize, memory: usize, passes: usize) -> Result<Self, Mirror256Error> {
+        check_depth(depth)?;
+        check_range("m", memory, 1, MAX_MEMORY_BLOCKS)?;
+        check_range("t", passes, 1, MAX_PASSES)?;
+        Ok(PasswordParams { depth, memory, passes })
+    }
+
+    /// Number of layers of the underlying hash
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Number of 32-byte memory blocks
+    pub fn memory(&self) -> usize {
+        self.memory
+    }
+
+    /// Number of mixing passes over memory
+    pub fn passes(&self) -> usize {
+        self.passes
+    }
+}
+
+fn check_range(name: &'static str, value: usize, min: usize, max: usize) -> Result<(), Mirror256Error> {
+    if value < min || value > max {
+        return Err(Mirror256Error::PasswordParameterOutOfRange { name, value, min, max });
+    }
+    Ok(())
+}
+
+/// A parsed PHC string: parameters, salt and hash
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PasswordHash {
+    pub params: PasswordParams,
+    pub salt: Vec<u8>,
+    pub hash: Vec<u8>,
+}
+
+impl PasswordHash {
+    /// Parse a `$mirror256$d=..,m=..,t=..$salt$hash` string
+    pub fn parse(phc: &str) -> Result<Self, Mirror256Error> {
+        let malformed = Mirror256Error::MalformedPasswordHash;
+        let fields: Vec<&str> = phc.split('$').collect();
+        let [empty, algorithm, params, salt, hash] = fields[..] else {
+            return Err(malformed);
+        };
+        if !empty.is_empty() || algorithm != ALGORITHM {
+            return Err(malformed);
+        }
+
+        // Parameters in their canonical order
+        let mut values = [0; 3];
+        let pairs: Vec<&str> = params.split(',').collect();
+        if pairs.len() != values.len() {
+            return Err(malformed);
+        }
+        for ((pair, name), value) in pairs.iter().zip(["d", "m", "t"]).zip(&mut values) {
+            let digits = pair.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')).ok_or(malformed.clone())?;
+            if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) || (digits.len() > 1 && digits.starts_with('0')) {
+                return Err(malformed);
+            }
+            *value = digits.parse().map_err(|_| malformed.clone())?;
+        }
+        let [depth, memory, passes] = values;
+
+        let salt = base64_decode(salt).ok_or(malformed.clone())?;
+        let hash = base64_decode(hash).ok_or(malformed.clone())?;
+        check_salt(&salt)?;
+        if hash.len() != Mirror256::new().output_size() {
+            return Err(malformed);
+        }
+
+        Ok(PasswordHash { params: PasswordParams::new(depth, memory, passes)?, salt, hash })
+    }
+}
+
+impl fmt::Display for PasswordHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let PasswordParams { depth, memory, passes } = self.params;
+        write!(
+            f,
+            "${}$d={},m={},t={}${}${}",
+            ALGORITHM,
+            depth,
+            memory,
+            passes,
+            base64_encode(&self.salt),
+            base64_encode(&self.hash)
+        )
+    }
+}
+
+fn check_salt(salt: &[u8]) -> Result<(), Mirror256Error> {
+    if salt.len() < MIN_SALT_BYTES {
+        return Err(Mirror256Error::SaltTooShort { len: salt.len(), min: MIN_SALT_BYTES });
+    }
+    if salt.len() > MAX_SALT_BYTES {
+        return Err(Mirror256Error::SaltTooLong { len: salt.len(), max: MAX_SALT_BYTES });
+    }
+    Ok(())
+}
+
+/// The raw 32-byte password hash
+pub fn derive(password: &[u8], salt: &[u8], params: &PasswordParams) -> Result<Digest, Mirror256Error> {
+    check_salt(salt)?;
+    let hasher = Mirror256::builder()
+        .depth(params.depth)
+        .compression(Compression::MiyaguchiPreneel)
+        .build()?;
+    let hash = |data: &[&[u8]]| -> [u8; BLOCK_BYTES] {
+        let mut h = hasher.clone();
+        for part in data {
+            h.update(part);
+        }
+        h.finalize().try_into().expect("standard digests are 32 bytes")
+    };
+    let length = |bytes: &[u8]| (bytes.len() as u32).to_be_bytes();
+
+    // Parameters and inputs, each length-prefixed
+    let mut x = hash(&[
+        PASSWORD_DOMAIN,
+        &(params.depth as u32).to_be_bytes(),
+        &(params.memory as u32).to_be_bytes(),
+        &(params.passes as u32).to_be_bytes(),
+        &length(salt),
+        salt,
+        &length(password),
+        password,
+    ]);
+
+    let mut memory: Vec<[u8; BLOCK_BYTES]> = Vec::with_capacity(params.memory);
+    for _ in 0..params.memory {
+        let next = hash(&[&x]);
+        memory.push(std::mem::replace(&mut x, next));
+    }
+
+    for _ in 0..params.passes {
+        for _ in 0..params.memory {
+            let j = (u64::from_le_bytes(x[..8].try_into().expect("digest has 8 bytes")) % params.memory as u64) as usize;
+            let mixed: [u8; BLOCK_BYTES] = std::array::from_fn(|i| x[i] ^ memory[j][i]);
+            x = hash(&[&mixed]);
+        }
+    }
+    Ok(x.to_vec())
+}
+
+/// Hash a password into a PHC string
+pub fn hash_password(password: &[u8], salt: &[u8], params: &PasswordParams) -> Result<String, Mirror256Error> {
+    let hash = derive(password, salt, params)?;
+    Ok(PasswordHash { params: *params, salt: salt.to_vec(), hash }.to_string())
+}
+
+/// Check a password against a PHC string, comparing hashes in constant time
+///
+/// A stored string names its own cost, so each of its parameters must be at most
+/// the one in `max_cost`, usually the parameters new hashes are made with; an
+/// untrusted string could otherwise demand up to `MAX_MEMORY_BLOCKS` blocks and
+/// `MAX_PASSES` passes.
+pub fn verify_password(password: &[u8], phc: &str, max_cost: &PasswordParams) -> Result<(), Mirror256Error> {
+    let stored = PasswordHash::parse(phc)?;
+    check_range("d", stored.params.depth, 1, max_cost.depth)?;
+    check_range("m", stored.params.memory, 1, max_cost.memory)?;
+    check_range("t", stored.params.passes, 1, max_cost.passes)?;
+    let hash = derive(password, &stored.salt, &stored.params)?;
+    if constant_time_eq(&hash, &stored.hash) {
+        Ok(())
+    } else {
+        Err(Mirror256Error::PasswordMismatch)
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard base64 without padding, as PHC strings use
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |acc, (i, &b)| acc | u32::from(b) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            out.push(BASE64_ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    out
+}
+
+/// Decode unpadded standard base64, rejecting other characters and unused bits
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    for chunk in text.as_bytes().chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let mut group = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let value = BASE64_ALPHABET.iter().position(|&a| a == c)? as u32;
+            group |= value << (18 - 6 * i);
+        }
+        let bytes = chunk.len() - 1;
+        if group & ((1 << (24 - 8 * bytes)) - 1) != 0 {
+            return None;
+        }
+        out.extend_from_slice(&group.to_be_bytes()[1..=bytes]);
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_roundtrip() {
+        // RFC 4648 vectors without padding
+        let vectors = [("", ""), ("f", "Zg"), ("fo", "Zm8"), ("foo", "Zm9v"), ("foob", "Zm9vYg"), ("fooba", "Zm9vYmE"), ("foobar", "Zm9vYmFy")];
+        for (plain, encoded) in vectors {
+            assert_eq!(base64_encode(plain.as_bytes()), encoded);
+            assert_eq!(base64_decode(encoded).unwrap(), plain.as_bytes());
+        }
+
+        let bytes: Vec<u8> = (0..=255).collect();
+        assert_eq!(base64_decode(&base64_encode(&bytes)).unwrap(), bytes);
+
+        assert_eq!(base64_decode("Zm9v="), None);
+        assert_eq!(base64_decode("Z"), None);
+        assert_eq!(base64_decode("Zh"), None);
+        assert_eq!(base64_decode("Zm-v"), None);
+    }
+}
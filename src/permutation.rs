@@ -0,0 +1,380 @@
+//! Bit-sliced evaluation of the gate network
+//!
+//! The state is packed into 64-bit words with wire `w` at bit `w % 64` of word
+//! `w / 64`, so nibble `n` of the original formulation occupies bits `4 * (n % 16)`
+//! to `4 * (n % 16) + 3` of word `n / 16`. A sublayer's gates sit on disjoint runs
+//! of three wires starting every fourth wire, so after rotating the state by the
+//! sublayer offset every gate reads bits 0 to 2 of one nibble. Gate types become
+//! masks of nibble low bits and a whole sublayer is a handful of word operations.
+
+use crate::simd::LaneWord;
+use crate::MAX_SIZE;
+
+/// Largest state in 64-bit words
+pub(crate) const MAX_WORDS: usize = MAX_SIZE / 64;
+
+/// Bit 0 of every nibble
+const NIBBLE_LOW_BITS: u64 = 0x1111_1111_1111_1111;
+
+/// Word layout of a state of `bits` wires
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Width {
+    bits: usize,
+    words: usize,
+    top_mask: u64,
+}
+
+impl Width {
+    /// Layout for a state size in bits, which must be a multiple of 8
+    pub(crate) fn new(bits: usize) -> Self {
+        let words = bits.div_ceil(64);
+        let top_bits = bits - 64 * (words - 1);
+        let top_mask = if top_bits == 64 { !0 } else { (1 << top_bits) - 1 };
+        Width { bits, words, top_mask }
+    }
+
+    /// State size in bits
+    pub(crate) fn bits(&self) -> usize {
+        self.bits
+    }
+
+    /// Number of 64-bit words holding the state
+    pub(crate) fn words(&self) -> usize {
+        self.words
+    }
+
+    /// Bits of the top word that hold wires
+    fn top_bits(&self) -> u32 {
+        (self.bits - 64 * (self.words - 1)) as u32
+    }
+
+    /// Mask of the positions of a word that hold wires
+    fn word_mask(&self, k: usize) -> u64 {
+        if k + 1 == self.words {
+            self.top_mask
+        } else {
+            !0
+        }
+    }
+}
+
+/// Pack a block of `bits / 8` bytes into state words; byte `i` holds nibbles `2i` (high) and `2i + 1` (low)
+pub(crate) fn unpack(width: Width, m: &[u8], state: &mut [u64]) {
+    state[..width.words].fill(0);
+    for (i, &b) in m.iter().take(width.bits / 8).enumerate() {
+        // Swap nibbles so the high one lands at the lower wire positions
+        state[i / 8] |= u64::from(b.rotate_left(4)) << (8 * (i % 8));
+    }
+}
+
+/// Unpack state words into `bits / 8` bytes, the inverse of `unpack`
+pub(crate) fn pack(width: Width, state: &[u64]) -> Vec<u8> {
+    (0..width.bits / 8)
+        .map(|i| {
+            let b = (state[i / 8] >> (8 * (i % 8))) as u8;
+            b.rotate_left(4)
+        })
+        .collect()
+}
+
+/// Pack a vector of nibbles into state words
+pub(crate) fn from_nibbles(width: Width, nibbles: &[u8]) -> Vec<u64> {
+    let mut state = vec![0; width.words];
+    for (n, &nibble) in nibbles.iter().take(width.bits / 4).enumerate() {
+        state[n / 16] |= u64::from(nibble & 0xF) << (4 * (n % 16));
+    }
+    state
+}
+
+/// Unpack state words into a vector of nibbles
+#[cfg(test)]
+pub(crate) fn to_nibbles(width: Width, state: &[u64]) -> Vec<u8> {
+    (0..width.bits / 4)
+        .map(|n| ((state[n / 16] >> (4 * (n % 16))) & 0xF) as u8)
+        .collect()
+}
+
+/// A layer encoding compiled for evaluation: the encoding itself followed, for each
+/// sublayer, by the masks of nibble low bits where each of the four gate kinds sits
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct LayerCode {
+    words: Vec<u64>,
+}
+
+/// Gate kinds in mask order
+const TOFFOLI: usize = 0;
+const FREDKIN: usize = 1;
+const TOFFOLI_MIRRORED: usize = 2;
+const FREDKIN_MIRRORED: usize = 3;
+
+impl LayerCode {
+    /// Compile a layer encoding given as state words
+    pub(crate) fn compile(width: Width, encoding: &[u64]) -> Self {
+        let mut code = LayerCode { words: vec![0; 9 * width.words] };
+        code.recompile(width, encoding);
+        code
+    }
+
+    /// Compile another encoding of the same width in place, reusing the allocation
+    pub(crate) fn recompile(&mut self, width: Width, encoding: &[u64]) {
+        let w = width.words;
+        let words = &mut self.words;
+        words[..w].copy_from_slice(&encoding[..w]);
+
+        for (sublayer, type_shift) in [0, 2].into_iter().enumerate() {
+            for k in 0..w {
+                let gates = NIBBLE_LOW_BITS & width.word_mask(k);
+
+                // Gate name (Toffoli or Fredkin) and symmetry (regular or mirrored)
+                let name = (encoding[k] >> type_shift) & gates;
+                let symmetry = (encoding[k] >> (type_shift + 1)) & gates;
+
+                let masks = w * (1 + 4 * sublayer);
+                words[masks + TOFFOLI * w + k] = gates & !name & !symmetry;
+                words[masks + FREDKIN * w + k] = name & !symmetry;
+                words[masks + TOFFOLI_MIRRORED * w + k] = !name & symmetry;
+                words[masks + FREDKIN_MIRRORED * w + k] = name & symmetry;
+            }
+        }
+    }
+
+    /// The layer encoding as state words
+    pub(crate) fn encoding(&self) -> &[u64] {
+        &self.words[..self.words.len() / 9]
+    }
+
+    /// The encoding and gate masks, `9 * words` in all
+    pub(crate) fn words(&self) -> &[u64] {
+        &self.words
+    }
+}
+
+/// Run the state through one layer per compiled encoding, the first encoding first
+pub(crate) fn permute<'a>(width: Width, state: &mut [u64], codes: impl IntoIterator<Item = &'a LayerCode>) {
+    for (layer, code) in codes.into_iter().enumerate() {
+        apply_layer(width, state, code, layer);
+    }
+}
+
+/// Undo `permute` with the same compiled encodings
+pub(crate) fn unpermute(width: Width, state: &mut [u64], codes: &[LayerCode]) {
+    for (layer, code) in codes.iter().enumerate().rev() {
+        unapply_layer(width, state, code, layer);
+    }
+}
+
+/// Apply a single hashing layer given an uncompiled encoding
+#[cfg(test)]
+pub(crate) fn layer_pass(width: Width, state: &mut [u64], encoding: &[u64], layer: usize) {
+    apply_layer(width, state, &LayerCode::compile(width, encoding), layer);
+}
+
+/// Apply a single hashing layer: XOR with the encoding, then both gate sublayers
+pub(crate) fn apply_layer(width: Width, state: &mut [u64], code: &LayerCode, layer: usize) {
+    apply_code(width, state, code.words(), layer);
+}
+
+/// Apply a single hashing layer to lane words, given the lane words of a compiled
+/// `LayerCode`: the encoding followed by the masks of both sublayers
+#[inline(always)]
+pub(crate) fn apply_code<V: LaneWord>(width: Width, state: &mut [V], code: &[V], layer: usize) {
+    let w = width.words;
+    let state = &mut state[..w];
+    for (word, &key) in state.iter_mut().zip(&code[..w]) {
+        *word = word.xor(key);
+    }
+
+    let initial_offset = (layer % 2) as u32;
+    sublayer(width, state, &code[w..5 * w], initial_offset);
+    sublayer(width, state, &code[5 * w..9 * w], initial_offset + 2);
+}
+
+/// Undo a single hashing layer; every gate is its own inverse, so this runs the
+/// sublayers in reverse order and then removes the encoding
+fn unapply_layer(width: Width, state: &mut [u64], code: &LayerCode, layer: usize) {
+    let w = width.words;
+    let code = code.words();
+    let state = &mut state[..w];
+
+    let initial_offset = (layer % 2) as u32;
+    sublayer(width, state, &code[5 * w..9 * w], initial_offset + 2);
+    sublayer(width, state, &code[w..5 * w], initial_offset);
+
+    for (word, &key) in state.iter_mut().zip(&code[..w]) {
+        *word ^= key;
+    }
+}
+
+/// Apply every gate of a sublayer whose gates start at wire `offset`, given its
+/// gate masks in `TOFFOLI`, `FREDKIN`, ... order
+#[inline(always)]
+fn sublayer<V: LaneWord>(width: Width, state: &mut [V], masks: &[V], offset: u32) {
+    let w = width.words;
+    // The state holds lane words, so constructing more is sound
+    let mut rotated = [state[0]; MAX_WORDS];
+    let rotated = &mut rotated[..w];
+    rotate_right(width, state, offset, rotated);
+
+    // SAFETY: as above
+    let low_bits = unsafe { V::splat(NIBBLE_LOW_BITS) };
+    for (k, word) in rotated.iter_mut().enumerate() {
+        let toffoli = masks[TOFFOLI * w + k];
+        let fredkin = masks[FREDKIN * w + k];
+        let toffoli_mirrored = masks[TOFFOLI_MIRRORED * w + k];
+        let fredkin_mirrored = masks[FREDKIN_MIRRORED * w + k];
+
+        let val1 = word.and(low_bits);
+        let val2 = word.shr(1).and(low_bits);
+        let val3 = word.shr(2).and(low_bits);
+
+        // Toffoli flips its target when both controls are set; Fredkin swaps its
+        // targets when the control is set, which flips both exactly when they differ
+        let fredkin_swap = fredkin.and(val1).and(val2.xor(val3));
+        let fredkin_mirrored_swap = fredkin_mirrored.and(val3).and(val1.xor(val2));
+        let flip1 = toffoli_mirrored.and(val2).and(val3).or(fredkin_mirrored_swap);
+        let flip2 = fredkin_swap.or(fredkin_mirrored_swap);
+        let flip3 = toffoli.and(val1).and(val2).or(fredkin_swap);
+
+        *word = word.xor(flip1.or(flip2.shl(1)).or(flip3.shl(2)));
+    }
+
+    rotate_left(width, rotated, offset, state);
+}
+
+/// The state rotated one nibble towards higher wires
+pub(crate) fn rotate_nibble(width: Width, x: &[u64]) -> [u64; MAX_WORDS] {
+    let mut out = [0u64; MAX_WORDS];
+    rotate_left(width, x, 4, &mut out);
+    out
+}
+
+/// `out` bit `j` = `x` bit `(j + by) mod bits`, for `by` below 8
+#[inline(always)]
+fn rotate_right<V: LaneWord>(width: Width, x: &[V], by: u32, out: &mut [V]) {
+    let last = width.words - 1;
+    if by == 0 {
+        out[..=last].copy_from_slice(&x[..=last]);
+        return;
+    }
+    for k in 0..last {
+        out[k] = x[k].shr(by).or(x[k + 1].shl(64 - by));
+    }
+    // SAFETY: `x` holds lane words, so constructing more is sound
+    let top_mask = unsafe { V::splat(width.top_mask) };
+    out[last] = x[last].shr(by).or(x[0].shl(width.top_bits() - by)).and(top_mask);
+}
+
+/// `out` bit `j` = `x` bit `(j - by) mod bits`, for `by` below 8
+#[inline(always)]
+fn rotate_left<V: LaneWord>(width: Width, x: &[V], by: u32, out: &mut [V]) {
+    let last = width.words - 1;
+    if by == 0 {
+        out[..=last].copy_from_slice(&x[..=last]);
+        return;
+    }
+    out[0] = x[0].shl(by).or(x[last].shr(width.top_bits() - by));
+    for k in 1..=last {
+        out[k] = x[k].shl(by).or(x[k - 1].shr(64 - by));
+    }
+    // SAFETY: `x` holds lane words, so constructing more is sound
+    let top_mask = unsafe { V::splat(width.top_mask) };
+    out[last] = out[last].and(top_mask);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reference;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    const SIZES: [usize; 6] = [128, 224, 256, 384, 512, 1024];
+
+    fn random_nibbles(rng: &mut StdRng, size: usize) -> Vec<u8> {
+        (0..size / 4).map(|_| rng.gen_range(0..16)).collect()
+    }
+
+    #[test]
+    fn test_pack_roundtrip() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for size in SIZES {
+            let width = Width::new(size);
+            let bytes: Vec<u8> = (0..size / 8).map(|_| rng.gen()).collect();
+            let mut state = [0u64; MAX_WORDS];
+            unpack(width, &bytes, &mut state);
+
+            assert_eq!(pack(width, &state), bytes);
+            assert_eq!(to_nibbles(width, &state), reference::unpack(size, &bytes));
+            assert_eq!(from_nibbles(width, &reference::unpack(size, &bytes)), state[..width.words()]);
+        }
+    }
+
+    #[test]
+    fn test_rotations_are_inverse() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for size in SIZES {
+            let width = Width::new(size);
+            let state = from_nibbles(width, &random_nibbles(&mut rng, size));
+            for by in 0..4 {
+                let mut rotated = vec![0; width.words()];
+                let mut back = vec![0; width.words()];
+                rotate_right(width, &state, by, &mut rotated);
+                rotate_left(width, &rotated, by, &mut back);
+                assert_eq!(back, state);
+            }
+        }
+    }
+
+    #[test]
+    fn test_layer_matches_reference() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for size in SIZES {
+            let width = Width::new(size);
+            for layer in 0..8 {
+                let block = random_nibbles(&mut rng, size);
+                let encoding = random_nibbles(&mut rng, size);
+
+                let mut state = from_nibbles(width, &block);
+                layer_pass(width, &mut state, &from_nibbles(width, &encoding), layer);
+
+                let expected = reference::hash_layer_pass(size, layer, &encoding, &block);
+                assert_eq!(to_nibbles(width, &state), expected, "size {} layer {}", size, layer);
+            }
+        }
+    }
+
+    #[test]
+    fn test_network_matches_reference() {
+        let mut rng = StdRng::seed_from_u64(4);
+        for size in SIZES {
+            let width = Width::new(size);
+            let encodings: Vec<Vec<u8>> = (0..16).map(|_| random_nibbles(&mut rng, size)).collect();
+            let codes: Vec<LayerCode> = encodings.iter().map(|e| LayerCode::compile(width, &from_nibbles(width, e))).collect();
+            let block: Vec<u8> = (0..size / 8).map(|_| rng.gen()).collect();
+
+            let mut state = [0u64; MAX_WORDS];
+            unpack(width, &block, &mut state);
+            permute(width, &mut state, &codes);
+
+            let expected = reference::mirror256_process(size, &encodings, &block);
+            assert_eq!(pack(width, &state), reference::pack(size, &expected), "size {}", size);
+        }
+    }
+
+    #[test]
+    fn test_unpermute_inverts_reference() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for size in SIZES {
+            let width = Width::new(size);
+            let encodings: Vec<Vec<u8>> = (0..9).map(|_| random_nibbles(&mut rng, size)).collect();
+            let codes: Vec<LayerCode> = encodings.iter().map(|e| LayerCode::compile(width, &from_nibbles(width, e))).collect();
+            let block: Vec<u8> = (0..size / 8).map(|_| rng.gen()).collect();
+
+            let output = reference::pack(size, &reference::mirror256_process(size, &encodings, &block));
+            let mut state = [0u64; MAX_WORDS];
+            unpack(width, &output, &mut state);
+            unpermute(width, &mut state, &codes);
+            assert_eq!(pack(width, &state), block, "size {}", size);
+        }
+    }
+}
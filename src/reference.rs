@@ -0,0 +1,149 @@
+//! Nibble-at-a-time implementation of the gate network
+//!
+//! This is the original formulation of Mirror256: the state is a vector of nibbles
+//! and every gate reads and writes its three wires one bit at a time. It is far too
+//! slow for hashing but easy to check against the specification, so the bit-sliced
+//! network in `permutation` is tested against it.
+
+// Gate types
+const TOFFOLI: u8 = 0;
+const FREDKIN: u8 = 1;
+
+// Gate symmetry
+const REGULAR: u8 = 0;
+const MIRRORED: u8 = 1;
+
+/// Unpack a block of bytes into an array of `size / 4` nibbles
+pub(crate) fn unpack(size: usize, m: &[u8]) -> Vec<u8> {
+    let mut ret = vec![0; size / 4];
+
+    let mut i = 0;
+    for &b in m.iter().take(size / 8) {
+        // high nibble
+        ret[i] = (b >> 4) & 0x0F;
+        i += 1;
+        // low nibble
+        ret[i] = b & 0x0F;
+        i += 1;
+    }
+    ret
+}
+
+/// Pack an array of nibbles into a byte array
+pub(crate) fn pack(size: usize, hm: &[u8]) -> Vec<u8> {
+    let mut hb = vec![0; size / 8];
+    for (i, b) in hb.iter_mut().enumerate() {
+        *b = (hm[i * 2] << 4) | hm[i * 2 + 1];
+    }
+    hb
+}
+
+/// Run a block through one layer per encoding, `encodings[0]` first
+pub(crate) fn mirror256_process(size: usize, encodings: &[Vec<u8>], m: &[u8]) -> Vec<u8> {
+    let mut block = unpack(size, m);
+
+    // Apply all hash layers
+    for (layer, layer_hash) in encodings.iter().enumerate() {
+        block = hash_layer_pass(size, layer, layer_hash, &block);
+    }
+
+    block
+}
+
+/// Apply a single hashing layer
+pub(crate) fn hash_layer_pass(size: usize, layer: usize, layer_hash: &[u8], block: &[u8]) -> Vec<u8> {
+    let mut block = block.to_vec();
+
+    // First XOR with layer encoding to avoid 0 to 0 hashes
+    for (nibble, encoding) in block.iter_mut().zip(layer_hash) {
+        *nibble ^= encoding;
+    }
+
+    // First sublayer
+    for (gate_index, &encoding) in layer_hash.iter().enumerate() {
+        let gate_type = encoding & 0x3;
+
+        let gate_name = gate_type & 1;  // Toffoli or Fredkin
+        let gate_symmetry = gate_type >> 1;  // Regular or Mirrored
+
+        block = apply_gate(size, gate_index, gate_name, gate_symmetry, &block, true, layer);
+    }
+
+    // Second sublayer
+    for (gate_index, &encoding) in layer_hash.iter().enumerate() {
+        let gate_type = (encoding & 0xC) >> 2;
+
+        let gate_name = gate_type & 1;  // Toffoli or Fredkin
+        let gate_symmetry = gate_type >> 1;  // Regular or Mirrored
+
+        block = apply_gate(size, gate_index, gate_name, gate_symmetry, &block, false, layer);
+    }
+
+    block
+}
+
+/// Get the wire index for a gate
+fn get_wire(size: usize, gate_index: usize, first_sublayer: bool, offset: usize) -> usize {
+    (gate_index * 4 + offset + (if !first_sublayer { 2 } else { 0 })) % size
+}
+
+/// Get the bit value at a specific wire
+fn get_bit(block: &[u8], wire: usize) -> u8 {
+    (block[wire / 4] >> (wire % 4)) & 1
+}
+
+/// Set the bit value at a specific wire
+fn set_bit(block: &mut [u8], wire: usize, bit: u8) {
+    let old_nib = block[wire / 4];
+    let ret = (old_nib & (15 ^ (1 << (wire % 4)))) | (bit << (wire % 4));
+    block[wire / 4] = ret;
+}
+
+/// Apply a gate (Toffoli or Fredkin) to the block
+fn apply_gate(size: usize, gate_index: usize, gate_name: u8, gate_symmetry: u8, block: &[u8], first_sublayer: bool, layer: usize) -> Vec<u8> {
+    let initial_offset = layer % 2;
+    let wire1 = get_wire(size, gate_index, first_sublayer, initial_offset);
+    let wire2 = get_wire(size, gate_index, first_sublayer, initial_offset + 1);
+    let wire3 = get_wire(size, gate_index, first_sublayer, initial_offset + 2);
+
+    let val1 = get_bit(block, wire1);
+    let val2 = get_bit(block, wire2);
+    let val3 = get_bit(block, wire3);
+
+    let mut oval1 = val1;
+    let mut oval2 = val2;
+    let mut oval3 = val3;
+
+    // Toffoli and Regular
+    if gate_name == TOFFOLI && gate_symmetry == REGULAR && (val1 == 1 && val2 == 1) {
+        oval3 = val3 ^ (val1 & val2);
+    }
+    // Toffoli and Mirrored
+    else if gate_name == TOFFOLI && gate_symmetry == MIRRORED && (val2 == 1 && val3 == 1) {
+        oval1 = val1 ^ (val2 & val3);
+    }
+    // Fredkin and Regular
+    else if gate_name == FREDKIN && gate_symmetry == REGULAR && val1 == 1 && val2 != val3 {
+        oval2 = val3;
+        oval3 = val2;
+    }
+    // Fredkin and Mirrored
+    else if gate_name == FREDKIN && gate_symmetry == MIRRORED && val3 == 1 && val1 != val2 {
+        oval1 = val2;
+        oval2 = val1;
+    }
+
+    let mut result = block.to_vec();
+
+    if val1 != oval1 {
+        set_bit(&mut result, wire1, oval1);
+    }
+    if val2 != oval2 {
+        set_bit(&mut result, wire2, oval2);
+    }
+    if val3 != oval3 {
+        set_bit(&mut result, wire3, oval3);
+    }
+
+    result
+}
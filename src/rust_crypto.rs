@@ -0,0 +1,67 @@
+//! RustCrypto `digest` traits
+//!
+//! `Mirror256Core` is the standard Mirror256 as a type of its own, for code generic
+//! over `digest::Digest`, such as `hmac::SimpleHmac` or `hkdf::SimpleHkdf`. Mirror256
+//! buffers bytes itself, so the core implements the byte-level traits directly
+//! rather than the block-level `core_api`.
+//!
+//! ```
+//! use mirror_hash::digest::Digest;
+//! use mirror_hash::{Mirror256, Mirror256Core};
+//!
+//! let digest = Mirror256Core::digest(b"data");
+//! assert_eq!(digest[..], Mirror256::new_from_bytes(b"data").finalize()[..]);
+//! ```
+
+use crate::Mirror256;
+use core::fmt;
+use digest::consts::U32;
+use digest::core_api::BlockSizeUser;
+use digest::{FixedOutput, FixedOutputReset, HashMarker, Output, OutputSizeUser, Reset, Update};
+
+/// Standard Mirror256 behind the RustCrypto `digest` traits: 32-byte blocks and
+/// 32-byte digests
+#[derive(Clone, Default)]
+pub struct Mirror256Core {
+    inner: Mirror256,
+}
+
+impl fmt::Debug for Mirror256Core {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Mirror256Core { ... }")
+    }
+}
+
+impl HashMarker for Mirror256Core {}
+
+impl BlockSizeUser for Mirror256Core {
+    type BlockSize = U32;
+}
+
+impl OutputSizeUser for Mirror256Core {
+    type OutputSize = U32;
+}
+
+impl Update for Mirror256Core {
+    fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+}
+
+impl FixedOutput for Mirror256Core {
+    fn finalize_into(self, out: &mut Output<Self>) {
+        out.copy_from_slice(&self.inner.finalize());
+    }
+}
+
+impl Reset for Mirror256Core {
+    fn reset(&mut self) {
+        self.inner = Mirror256::new();
+    }
+}
+
+impl FixedOutputReset for Mirror256Core {
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        out.copy_from_slice(&core::mem::take(&mut self.inner).finalize());
+    }
+}
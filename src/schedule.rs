@@ -0,0 +1,190 @@
+//! Compiled gate schedules
+//!
+//! Layer `l` of a block is driven by the `l`-th most recent block hash, or by the
+//! initial state once the message has fewer than `depth` blocks behind it. Each
+//! encoding is compiled into a `LayerCode` once, when it enters the window, so
+//! hashing a block never re-derives gate types. The compiled initial state depends
+//! only on the parameter set, so it is built once and shared by every hasher.
+
+use crate::permutation::{self, LayerCode, Width, MAX_WORDS};
+use crate::InitialState;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Compiled layer encodings for the next block, most recent block hash first
+#[derive(Clone, Debug)]
+pub(crate) struct GateSchedule {
+    depth: usize,
+    initial: Arc<[LayerCode]>,
+    recent: VecDeque<LayerCode>,
+}
+
+impl GateSchedule {
+    /// Schedule of a hasher that has not hashed any block yet
+    pub(crate) fn new(initial: Arc<[LayerCode]>) -> Self {
+        GateSchedule {
+            depth: initial.len(),
+            initial,
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// Compiled encodings of all layers, layer 0 first
+    pub(crate) fn layers(&self) -> impl Iterator<Item = &LayerCode> {
+        let from_initial = self.depth - self.recent.len();
+        self.recent.iter().chain(self.initial[..from_initial].iter())
+    }
+
+    /// Compiled encoding of one layer
+    pub(crate) fn layer(&self, layer: usize) -> &LayerCode {
+        match self.recent.get(layer) {
+            Some(code) => code,
+            None => &self.initial[layer - self.recent.len()],
+        }
+    }
+
+    /// Number of layers
+    pub(crate) fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Encoding of layer 0: the most recent block hash, or the first initial encoding
+    pub(crate) fn latest(&self) -> &LayerCode {
+        self.recent.front().unwrap_or(&self.initial[0])
+    }
+
+    /// Make a block hash the encoding of layer 0, shifting the others one layer down
+    pub(crate) fn push(&mut self, width: Width, output: &[u64]) {
+        // Recycle the oldest encoding's slot in the ring
+        let recycled = if self.recent.len() == self.depth { self.recent.pop_back() } else { None };
+        let code = match recycled {
+            Some(mut code) => {
+                code.recompile(width, output);
+                code
+            }
+            None => LayerCode::compile(width, output),
+        };
+        self.recent.push_front(code);
+    }
+}
+
+/// Fill `codes` with the layers of a block under `KeySchedule::MessageDependent`:
+/// the chained encoding of layer `l` XOR the block and counter block rotated by
+/// `l + 1` nibbles
+///
+/// Layer 0 XORs its encoding into the block itself, so an unrotated block would
+/// cancel and leave layer 0 running on a constant.
+pub(crate) fn message_dependent(width: Width, schedule: &GateSchedule, block: &[u64], index: u64, codes: &mut Vec<LayerCode>) {
+    let mut counter = vec![0; width.bits() / 8];
+    let at = counter.len() - 8;
+    counter[at..].copy_from_slice(&index.to_be_bytes());
+    let mut words = [0u64; MAX_WORDS];
+    permutation::unpack(width, &counter, &mut words);
+    for (word, &b) in words.iter_mut().zip(block) {
+        *word ^= b;
+    }
+
+    let mut encoding = [0u64; MAX_WORDS];
+    codes.resize_with(schedule.depth(), || LayerCode::compile(width, &encoding));
+    for (code, chained) in codes.iter_mut().zip(schedule.layers()) {
+        words = permutation::rotate_nibble(width, &words);
+        for ((e, &c), &w) in encoding.iter_mut().zip(chained.encoding()).zip(&words) {
+            *e = c ^ w;
+        }
+        code.recompile(width, &encoding);
+    }
+}
+
+/// Parameters that determine a shared initial schedule
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct ScheduleKey {
+    depth: usize,
+    size: usize,
+    initial_state: InitialState,
+}
+
+/// Compiled initial schedule for a parameter set, built with `build` on first use
+pub(crate) fn shared_initial(
+    depth: usize,
+    size: usize,
+    initial_state: InitialState,
+    build: impl FnOnce() -> Vec<LayerCode>,
+) -> Arc<[LayerCode]> {
+    static CACHE: OnceLock<Mutex<HashMap<ScheduleKey, Arc<[LayerCode]>>>> = OnceLock::new();
+
+    let key = ScheduleKey { depth, size, initial_state };
+    let cache = CACHE.get_or_init(Default::default);
+    if let Some(initial) = cache.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+        return Arc::clone(initial);
+    }
+
+    // Compile outside the lock; a concurrent builder of the same key wastes its work
+    let initial: Arc<[LayerCode]> = build().into();
+    let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+    Arc::clone(cache.entry(key).or_insert(initial))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(width: Width, values: std::ops::Range<u64>) -> Vec<LayerCode> {
+        values.map(|v| LayerCode::compile(width, &[v, !v, v << 3, v >> 1])).collect()
+    }
+
+    #[test]
+    fn test_window_slides_over_initial_state() {
+        let width = Width::new(256);
+        let initial = codes(width, 0..4);
+        let mut schedule = GateSchedule::new(initial.clone().into());
+
+        let encodings = |schedule: &GateSchedule| -> Vec<Vec<u64>> {
+            schedule.layers().map(|code| code.encoding().to_vec()).collect()
+        };
+        assert_eq!(encodings(&schedule), initial.iter().map(|c| c.encoding().to_vec()).collect::<Vec<_>>());
+
+        // Pushed hashes take over layer 0 and push the initial encodings out of the window
+        let pushed = codes(width, 100..106);
+        for (i, code) in pushed.iter().enumerate() {
+            schedule.push(width, code.encoding());
+            let mut expected: Vec<Vec<u64>> = pushed[..=i].iter().rev().map(|c| c.encoding().to_vec()).collect();
+            expected.extend(initial.iter().map(|c| c.encoding().to_vec()));
+            expected.truncate(4);
+
+            assert_eq!(encodings(&schedule), expected);
+            assert_eq!(schedule.latest(), code);
+        }
+    }
+
+    #[test]
+    fn test_message_dependent_layer_0_depends_on_the_block() {
+        let width = Width::new(256);
+        let schedule = GateSchedule::new(codes(width, 0..4).into());
+        let layer_0 = |block: &[u64; 4]| -> Vec<u64> {
+            let mut layers = Vec::new();
+            message_dependent(width, &schedule, block, 0, &mut layers);
+            layers[0].encoding()[..4].to_vec()
+        };
+
+        // Layer 0 XORs its encoding into the block itself, so the gates see the block
+        // XOR the encoding, which must change with the block as well as the encoding
+        let zero = layer_0(&[0; 4]);
+        for bit in 0..256 {
+            let mut block = [0u64; 4];
+            block[bit / 64] = 1 << (bit % 64);
+            let encoding = layer_0(&block);
+            let input: Vec<u64> = block.iter().zip(&encoding).map(|(b, e)| b ^ e).collect();
+            assert_ne!(encoding, zero, "bit {}", bit);
+            assert_ne!(input, zero, "bit {}", bit);
+        }
+    }
+
+    #[test]
+    fn test_initial_schedule_is_shared() {
+        let width = Width::new(256);
+        let first = shared_initial(3, 256, InitialState::Seeded(3), || codes(width, 0..3));
+        let second = shared_initial(3, 256, InitialState::Seeded(3), || unreachable!("already cached"));
+
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+}
@@ -0,0 +1,274 @@
+//! Lane words for evaluating the gate network on several states at once
+//!
+//! The bit-sliced network only uses AND, OR, XOR and shifts within 64-bit words,
+//! so it runs unchanged on vectors holding word `k` of several independent states.
+//! A plain `u64` is the one-lane case used for single messages.
+
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+/// Most lanes of any lane word
+pub(crate) const MAX_LANES: usize = 4;
+
+/// A word of the bit-sliced state replicated across independent lanes
+///
+/// A value of a lane word may only exist while running on a CPU that supports the
+/// instructions behind it. The constructors are `unsafe` to uphold that; the other
+/// operations are safe because a value to call them on proves the support. Code
+/// that already holds a value of the lane word may construct more of them.
+pub(crate) trait LaneWord: Copy {
+    /// Number of independent states evaluated together
+    const LANES: usize;
+
+    /// The same value in every lane
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support the instructions of the lane word.
+    unsafe fn splat(value: u64) -> Self;
+
+    /// One value per lane from the first `LANES` entries of `values`
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support the instructions of the lane word.
+    unsafe fn load(values: &[u64]) -> Self;
+
+    /// Write one value per lane into the first `LANES` entries of `out`
+    fn store(self, out: &mut [u64]);
+
+    fn and(self, other: Self) -> Self;
+    fn or(self, other: Self) -> Self;
+    fn xor(self, other: Self) -> Self;
+    fn shr(self, by: u32) -> Self;
+    fn shl(self, by: u32) -> Self;
+}
+
+impl LaneWord for u64 {
+    const LANES: usize = 1;
+
+    #[inline(always)]
+    unsafe fn splat(value: u64) -> Self {
+        value
+    }
+
+    #[inline(always)]
+    unsafe fn load(values: &[u64]) -> Self {
+        values[0]
+    }
+
+    #[inline(always)]
+    fn store(self, out: &mut [u64]) {
+        out[0] = self;
+    }
+
+    #[inline(always)]
+    fn and(self, other: Self) -> Self {
+        self & other
+    }
+
+    #[inline(always)]
+    fn or(self, other: Self) -> Self {
+        self | other
+    }
+
+    #[inline(always)]
+    fn xor(self, other: Self) -> Self {
+        self ^ other
+    }
+
+    #[inline(always)]
+    fn shr(self, by: u32) -> Self {
+        self >> by
+    }
+
+    #[inline(always)]
+    fn shl(self, by: u32) -> Self {
+        self << by
+    }
+}
+
+/// Four lanes in plain words, left to the compiler to vectorize
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Portable([u64; 4]);
+
+impl Portable {
+    #[inline(always)]
+    fn map2(self, other: Self, f: impl Fn(u64, u64) -> u64) -> Self {
+        Portable([f(self.0[0], other.0[0]), f(self.0[1], other.0[1]), f(self.0[2], other.0[2]), f(self.0[3], other.0[3])])
+    }
+}
+
+impl LaneWord for Portable {
+    const LANES: usize = 4;
+
+    #[inline(always)]
+    unsafe fn splat(value: u64) -> Self {
+        Portable([value; 4])
+    }
+
+    #[inline(always)]
+    unsafe fn load(values: &[u64]) -> Self {
+        Portable([values[0], values[1], values[2], values[3]])
+    }
+
+    #[inline(always)]
+    fn store(self, out: &mut [u64]) {
+        out[..4].copy_from_slice(&self.0);
+    }
+
+    #[inline(always)]
+    fn and(self, other: Self) -> Self {
+        self.map2(other, |a, b| a & b)
+    }
+
+    #[inline(always)]
+    fn or(self, other: Self) -> Self {
+        self.map2(other, |a, b| a | b)
+    }
+
+    #[inline(always)]
+    fn xor(self, other: Self) -> Self {
+        self.map2(other, |a, b| a ^ b)
+    }
+
+    #[inline(always)]
+    fn shr(self, by: u32) -> Self {
+        Portable(self.0.map(|a| a >> by))
+    }
+
+    #[inline(always)]
+    fn shl(self, by: u32) -> Self {
+        Portable(self.0.map(|a| a << by))
+    }
+}
+
+/// Two lanes in an SSE2 register
+///
+/// Values only exist on CPUs with SSE2, as required by the `LaneWord` constructors;
+/// they are only constructed on the path selected after checking for SSE2 at runtime.
+#[cfg(target_arch = "x86_64")]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Sse2(__m128i);
+
+#[cfg(target_arch = "x86_64")]
+impl LaneWord for Sse2 {
+    const LANES: usize = 2;
+
+    #[inline(always)]
+    unsafe fn splat(value: u64) -> Self {
+        // SAFETY: the caller guarantees SSE2 support
+        Sse2(unsafe { _mm_set1_epi64x(value as i64) })
+    }
+
+    #[inline(always)]
+    unsafe fn load(values: &[u64]) -> Self {
+        assert!(values.len() >= 2);
+        // SAFETY: the caller guarantees SSE2 support, `values` holds at least 16 bytes
+        // and the load is unaligned
+        Sse2(unsafe { _mm_loadu_si128(values.as_ptr().cast()) })
+    }
+
+    #[inline(always)]
+    fn store(self, out: &mut [u64]) {
+        assert!(out.len() >= 2);
+        // SAFETY: `self` exists, so SSE2 is supported; `out` holds at least 16 bytes
+        // and the store is unaligned
+        unsafe { _mm_storeu_si128(out.as_mut_ptr().cast(), self.0) }
+    }
+
+    #[inline(always)]
+    fn and(self, other: Self) -> Self {
+        // SAFETY: `self` exists, so SSE2 is supported
+        Sse2(unsafe { _mm_and_si128(self.0, other.0) })
+    }
+
+    #[inline(always)]
+    fn or(self, other: Self) -> Self {
+        // SAFETY: as above
+        Sse2(unsafe { _mm_or_si128(self.0, other.0) })
+    }
+
+    #[inline(always)]
+    fn xor(self, other: Self) -> Self {
+        // SAFETY: as above
+        Sse2(unsafe { _mm_xor_si128(self.0, other.0) })
+    }
+
+    #[inline(always)]
+    fn shr(self, by: u32) -> Self {
+        // SAFETY: as above
+        Sse2(unsafe { _mm_srl_epi64(self.0, _mm_cvtsi32_si128(by as i32)) })
+    }
+
+    #[inline(always)]
+    fn shl(self, by: u32) -> Self {
+        // SAFETY: as above
+        Sse2(unsafe { _mm_sll_epi64(self.0, _mm_cvtsi32_si128(by as i32)) })
+    }
+}
+
+/// Four lanes in an AVX2 register
+///
+/// Values only exist on CPUs with AVX2, as required by the `LaneWord` constructors;
+/// they are only constructed on the path selected after checking for AVX2 at runtime.
+#[cfg(target_arch = "x86_64")]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Avx2(__m256i);
+
+#[cfg(target_arch = "x86_64")]
+impl LaneWord for Avx2 {
+    const LANES: usize = 4;
+
+    #[inline(always)]
+    unsafe fn splat(value: u64) -> Self {
+        // SAFETY: the caller guarantees AVX2 support
+        Avx2(unsafe { _mm256_set1_epi64x(value as i64) })
+    }
+
+    #[inline(always)]
+    unsafe fn load(values: &[u64]) -> Self {
+        assert!(values.len() >= 4);
+        // SAFETY: the caller guarantees AVX2 support, `values` holds at least 32 bytes
+        // and the load is unaligned
+        Avx2(unsafe { _mm256_loadu_si256(values.as_ptr().cast()) })
+    }
+
+    #[inline(always)]
+    fn store(self, out: &mut [u64]) {
+        assert!(out.len() >= 4);
+        // SAFETY: `self` exists, so AVX2 is supported; `out` holds at least 32 bytes
+        // and the store is unaligned
+        unsafe { _mm256_storeu_si256(out.as_mut_ptr().cast(), self.0) }
+    }
+
+    #[inline(always)]
+    fn and(self, other: Self) -> Self {
+        // SAFETY: `self` exists, so AVX2 is supported
+        Avx2(unsafe { _mm256_and_si256(self.0, other.0) })
+    }
+
+    #[inline(always)]
+    fn or(self, other: Self) -> Self {
+        // SAFETY: as above
+        Avx2(unsafe { _mm256_or_si256(self.0, other.0) })
+    }
+
+    #[inline(always)]
+    fn xor(self, other: Self) -> Self {
+        // SAFETY: as above
+        Avx2(unsafe { _mm256_xor_si256(self.0, other.0) })
+    }
+
+    #[inline(always)]
+    fn shr(self, by: u32) -> Self {
+        // SAFETY: as above
+        Avx2(unsafe { _mm256_srl_epi64(self.0, _mm_cvtsi32_si128(by as i32)) })
+    }
+
+    #[inline(always)]
+    fn shl(self, by: u32) -> Self {
+        // SAFETY: as above
+        Avx2(unsafe { _mm256_sll_epi64(self.0, _mm_cvtsi32_si128(by as i32)) })
+    }
+}
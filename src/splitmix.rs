@@ -0,0 +1,50 @@
+//! SplitMix64, the generator behind seeded initial states
+//!
+//! SplitMix64 (Steele, Lea and Flood, 2014) adds a fixed odd constant to a 64-bit
+//! counter and scrambles the counter with two xor-shift-multiply rounds. It is a
+//! few lines of fully specified integer arithmetic, so seeded states stay the same
+//! on every platform and release, unlike generators borrowed from dependencies.
+
+/// Increment of the counter, the odd integer closest to `2^64 / phi`
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// The SplitMix64 generator
+#[derive(Clone, Debug)]
+pub(crate) struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(GOLDEN_GAMMA);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_outputs() {
+        // Outputs of the reference implementation seeded with 0 and with 1234567
+        let mut zero = SplitMix64::new(0);
+        assert_eq!(
+            [zero.next_u64(), zero.next_u64(), zero.next_u64()],
+            [0xe220a8397b1dcdaf, 0x6e789e6aa1b965f4, 0x06c45d188009454f]
+        );
+
+        let mut seeded = SplitMix64::new(1234567);
+        assert_eq!(
+            [seeded.next_u64(), seeded.next_u64(), seeded.next_u64(), seeded.next_u64(), seeded.next_u64()],
+            [6457827717110365317, 3203168211198807973, 9817491932198370423, 4593380528125082431, 16408922859458223821]
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Sponge and duplex constructions over the block permutation
+//!
+//! For fixed layer encodings the gate network is a bijection of the state, so it can
+//! serve as the permutation of a sponge. The state is split into a rate, its first
+//! `rate` bytes, which input is XORed into and output is read from, and a capacity,
+//! the remaining bytes, which only the permutation touches. Input is padded with a
+//! `0x01` byte, zeros, and `0x80` XORed into the last rate byte. Generic security is
+//! half the capacity in bits, given a permutation that mixes the whole state: the
+//! defaults run all `MAX_DEPTH` layers, as `MirrorAead` does.
+//!
+//! This is a separate family from `Mirror256`, whose chaining construction changes the
+//! gates with every block; the sponge keeps the gates of one `BlockPermutation` and
+//! carries its state from block to block instead.
+
+use crate::{BlockPermutation, Digest, Mirror256, Mirror256Error, MAX_DEPTH};
+use std::io;
+
+/// The bytes of input appended by the sponge padding
+const PAD_FIRST: u8 = 0x01;
+const PAD_LAST: u8 = 0x80;
+
+/// Smallest rate in bytes, which leaves a duplex call room for one byte of input
+const MIN_RATE: usize = 2;
+
+/// A permuted state with a rate, common to the sponge and duplex
+#[derive(Clone, Debug)]
+struct SpongeState {
+    permutation: BlockPermutation,
+    state: Vec<u8>,
+    rate: usize,
+    position: usize,
+}
+
+impl SpongeState {
+    fn new(permutation: BlockPermutation, capacity: usize) -> Result<Self, Mirror256Error> {
+        let size = permutation.size();
+        if capacity == 0 || !capacity.is_multiple_of(8) || capacity + 8 * MIN_RATE > size {
+            return Err(Mirror256Error::SpongeCapacity { capacity, size });
+        }
+        Ok(SpongeState {
+            state: vec![0; size / 8],
+            rate: (size - capacity) / 8,
+            permutation,
+            position: 0,
+        })
+    }
+
+    fn permute(&mut self) {
+        self.permutation.forward(&mut self.state);
+        self.position = 0;
+    }
+
+    /// XOR bytes into the rate, permuting whenever it is full
+    fn absorb(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.state[self.position] ^= byte;
+            self.position += 1;
+            if self.position == self.rate {
+                self.permute();
+            }
+        }
+    }
+
+    /// Pad the partial rate block and permute
+    fn pad(&mut self) {
+        self.state[self.position] ^= PAD_FIRST;
+        self.state[self.rate - 1] ^= PAD_LAST;
+        self.permute();
+    }
+
+    /// Read bytes from the rate, permuting whenever it is used up
+    fn squeeze(&mut self, out: &mut [u8]) {
+        for byte in out {
+            if self.position == self.rate {
+                self.permute();
+            }
+            *byte = self.state[self.position];
+            self.position += 1;
+        }
+    }
+}
+
+/// Sponge hash over the gate network of a fixed `BlockPermutation`
+///
+/// The digest is the first `size / 8` bytes of the output stream of `finalize_xof`.
+///
+/// ```
+/// use mirror_hash::MirrorSponge;
+///
+/// let mut sponge = MirrorSponge::new();
+/// sponge.update(b"hello world");
+/// let digest = sponge.clone().finalize();
+/// assert_eq!(digest, sponge.finalize_xof().squeeze(32));
+/// ```
+#[derive(Clone, Debug)]
+pub struct MirrorSponge {
+    inner: SpongeState,
+}
+
+impl Default for MirrorSponge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MirrorSponge {
+    /// Sponge over the standard 256-bit state with all `MAX_DEPTH` layers and a
+    /// 128-bit capacity
+    pub fn new() -> Self {
+        let permutation = Mirror256::builder()
+            .depth(MAX_DEPTH)
+            .build()
+            .expect("the full depth is valid")
+            .block_permutation();
+        let capacity = permutation.size() / 2;
+        Self::with_permutation(permutation, capacity).expect("half the state is a valid capacity")
+    }
+
+    /// Sponge over any permutation, with `capacity` bits of the state kept from the rate
+    ///
+    /// The capacity must be a positive multiple of 8 leaving a rate of at least 2 bytes.
+    pub fn with_permutation(permutation: BlockPermutation, capacity: usize) -> Result<Self, Mirror256Error> {
+        Ok(MirrorSponge { inner: SpongeState::new(permutation, capacity)? })
+    }
+
+    /// Bytes absorbed per permutation
+    pub fn rate(&self) -> usize {
+        self.inner.rate
+    }
+
+    /// Capacity in bits
+    pub fn capacity(&self) -> usize {
+        self.inner.permutation.size() - 8 * self.inner.rate
+    }
+
+    /// Digest length in bytes
+    pub fn output_size(&self) -> usize {
+        self.inner.permutation.size() / 8
+    }
+
+    /// Absorb data
+    pub fn update(&mut self, m: &[u8]) {
+        self.inner.absorb(m);
+    }
+
+    /// Pad the input and return the digest bytes
+    pub fn finalize(self) -> Digest {
+        let len = self.output_size();
+        self.finalize_xof().squeeze(len)
+    }
+
+    /// Pad the input and return a reader of unlimited output
+    pub fn finalize_xof(mut self) -> SpongeReader {
+        self.inner.pad();
+        SpongeReader { inner: self.inner }
+    }
+
+    /// Get the digest of the data absorbed so far as a byte array
+    pub fn digest(&self) -> Digest {
+        self.clone().finalize()
+    }
+
+    /// Get the digest as a hexadecimal string
+    pub fn hexdigest(&self) -> String {
+        format!("0x{}", hex::encode(self.digest()))
+    }
+}
+
+/// Reader of the output of `MirrorSponge::finalize_xof`
+#[derive(Clone, Debug)]
+pub struct SpongeReader {
+    inner: SpongeState,
+}
+
+impl SpongeReader {
+    /// Fill `out` with the next bytes of output
+    pub fn read(&mut self, out: &mut [u8]) {
+        self.inner.squeeze(out);
+    }
+
+    /// The next `len` bytes of output
+    pub fn squeeze(&mut self, len: usize) -> Vec<u8> {
+        let mut out = vec![0; len];
+        self.read(&mut out);
+        out
+    }
+}
+
+impl io::Read for SpongeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        SpongeReader::read(self, buf);
+        Ok(buf.len())
+    }
+}
+
+/// Duplex object over the gate network of a fixed `BlockPermutation`
+///
+/// Each call absorbs one padded input block and returns output that depends on every
+/// input so far, as needed by authenticated encryption and stateful generators.
+///
+/// ```
+/// use mirror_hash::MirrorDuplex;
+///
+/// let mut duplex = MirrorDuplex::new();
+/// let mut first = [0u8; 16];
+/// let mut second = [0u8; 16];
+/// duplex.duplex(b"key", &mut first);
+/// duplex.duplex(b"nonce", &mut second);
+/// assert_ne!(first, second);
+/// ```
+#[derive(Clone, Debug)]
+pub struct MirrorDuplex {
+    inner: SpongeState,
+}
+
+impl Default for MirrorDuplex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MirrorDuplex {
+    /// Duplex over the standard 256-bit state with all `MAX_DEPTH` layers and a
+    /// 128-bit capacity
+    pub fn new() -> Self {
+        MirrorDuplex { inner: MirrorSponge::new().inner }
+    }
+
+    /// Duplex over any permutation, with `capacity` bits of the state kept from the rate
+    pub fn with_permutation(permutation: BlockPermutation, capacity: usize) -> Result<Self, Mirror256Error> {
+        Ok(MirrorDuplex { inner: SpongeState::new(permutation, capacity)? })
+    }
+
+    /// Bytes of output per call
+    pub fn rate(&self) -> usize {
+        self.inner.rate
+    }
+
+    /// Longest input per call, one byte short of the rate to leave room for padding
+    pub fn max_input(&self) -> usize {
+        self.inner.rate - 1
+    }
+
+    /// Absorb one padded input block, permute, and read `output` from the rate
+    ///
+    /// # Panics
+    ///
+    /// If `input` is longer than `max_input()` or `output` longer than `rate()`.
+    pub fn duplex(&mut self, input: &[u8], output: &mut [u8]) {
+        assert!(input.len() <= self.max_input(), "duplex input must leave room for padding");
+        assert!(output.len() <= self.rate(), "duplex output must fit in the rate");
+        // Every call starts a fresh rate block
+        self.inner.position = 0;
+        self.inner.absorb(input);
+        self.inner.pad();
+        self.inner.squeeze(output);
+    }
+}
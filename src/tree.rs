@@ -0,0 +1,156 @@
+//! Tree hashing of large inputs
+//!
+//! The input is cut into leaf chunks of a fixed size; an empty input is a single
+//! empty leaf. Each leaf is hashed on its own as `0x00 ‖ chunk`, and nodes are then
+//! combined pairwise, level by level, as `0x01 ‖ left ‖ right` until one node is
+//! left. A node without a partner at the end of a level moves up unchanged. The
+//! prefixes keep leaves and parents apart, so no leaf can be passed off as a parent.
+//!
+//! Nodes on the same level are independent and are hashed together, on the thread
+//! pool when the `parallel` feature is enabled. The digest depends only on the
+//! input, the chunk size and the hasher parameters.
+
+use crate::{Digest, Mirror256, Mirror256Error};
+
+/// Leaf chunk size used by `TreeHasher::default`
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Domain separation prefixes of the two node kinds
+const LEAF: u8 = 0x00;
+const PARENT: u8 = 0x01;
+
+/// Leaves buffered before they are hashed as one batch
+const LEAVES_PER_BATCH: usize = 64;
+
+/// Streaming tree hasher over leaf chunks of a fixed size
+///
+/// ```
+/// use mirror_hash::{Mirror256, TreeHasher};
+///
+/// let mut tree = TreeHasher::new(&Mirror256::new(), 4096).unwrap();
+/// tree.update(&vec![0u8; 100_000]);
+/// println!("{}", tree.hexdigest());
+/// ```
+#[derive(Clone)]
+pub struct TreeHasher {
+    leaf: Mirror256,
+    parent: Mirror256,
+    chunk_size: usize,
+    parallel: bool,
+    buffer: Vec<u8>,
+    leaves: Vec<Digest>,
+}
+
+impl Default for TreeHasher {
+    fn default() -> Self {
+        Self::new(&Mirror256::new(), DEFAULT_CHUNK_SIZE).expect("default chunk size is valid")
+    }
+}
+
+impl TreeHasher {
+    /// Hash nodes with the parameters of `hasher` over leaves of `chunk_size` bytes
+    pub fn new(hasher: &Mirror256, chunk_size: usize) -> Result<Self, Mirror256Error> {
+        if chunk_size == 0 {
+            return Err(Mirror256Error::ZeroChunkSize);
+        }
+
+        let mut leaf = hasher.clone();
+        leaf.update(&[LEAF]);
+        let mut parent = hasher.clone();
+        parent.update(&[PARENT]);
+
+        Ok(TreeHasher {
+            leaf,
+            parent,
+            chunk_size,
+            parallel: cfg!(feature = "parallel"),
+            buffer: Vec::new(),
+            leaves: Vec::new(),
+        })
+    }
+
+    /// Whether to hash nodes on the thread pool (the default); the digest is the same either way
+    #[cfg(feature = "parallel")]
+    pub fn parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Leaf chunk size in bytes
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Absorb more data
+    pub fn update(&mut self, mut data: &[u8]) {
+        let batch_bytes = self.chunk_size * LEAVES_PER_BATCH;
+
+        if !self.buffer.is_empty() {
+            let take = (batch_bytes - self.buffer.len()).min(data.len());
+            self.buffer.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            if self.buffer.len() < batch_bytes {
+                return;
+            }
+            let buffer = std::mem::take(&mut self.buffer);
+            self.hash_leaves(&buffer);
+            self.buffer = buffer;
+            self.buffer.clear();
+        }
+
+        // Large inputs are hashed in place, keeping only the last partial batch
+        if data.len() >= batch_bytes {
+            let whole = data.len() - data.len() % self.chunk_size;
+            self.hash_leaves(&data[..whole]);
+            data = &data[whole..];
+        }
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// Consume the hasher and return the root digest
+    pub fn finalize(mut self) -> Digest {
+        let buffer = std::mem::take(&mut self.buffer);
+        self.hash_leaves(&buffer);
+        if self.leaves.is_empty() {
+            self.leaves = self.hash_nodes(&self.leaf, &[&[]]);
+        }
+
+        let mut nodes = std::mem::take(&mut self.leaves);
+        while nodes.len() > 1 {
+            let pairs: Vec<Vec<u8>> = nodes.chunks_exact(2).map(|pair| pair.concat()).collect();
+            let pairs: Vec<&[u8]> = pairs.iter().map(Vec::as_slice).collect();
+            let mut next = self.hash_nodes(&self.parent, &pairs);
+            if nodes.len() % 2 == 1 {
+                next.push(nodes.pop().expect("odd level is not empty"));
+            }
+            nodes = next;
+        }
+        nodes.pop().expect("a tree has a root")
+    }
+
+    /// Get the root digest of the data absorbed so far
+    pub fn digest(&self) -> Digest {
+        self.clone().finalize()
+    }
+
+    /// Get the root digest as a hexadecimal string
+    pub fn hexdigest(&self) -> String {
+        format!("0x{}", hex::encode(self.digest()))
+    }
+
+    /// Hash `data` as consecutive leaves; only the last chunk may be partial
+    fn hash_leaves(&mut self, data: &[u8]) {
+        let chunks: Vec<&[u8]> = data.chunks(self.chunk_size).collect();
+        let digests = self.hash_nodes(&self.leaf, &chunks);
+        self.leaves.extend(digests);
+    }
+
+    /// Digests of independent nodes, each hashed after the node prefix absorbed by `prefixed`
+    fn hash_nodes(&self, prefixed: &Mirror256, messages: &[&[u8]]) -> Vec<Digest> {
+        if self.parallel {
+            #[cfg(feature = "parallel")]
+            return prefixed.par_hash_many(messages);
+        }
+        prefixed.hash_many(messages)
+    }
+}
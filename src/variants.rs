@@ -0,0 +1,130 @@
+use crate::{Mirror256, XofReader};
+
+/// Define a fixed-width hash type wrapping a `Mirror256` hasher whose state size and
+/// block size are derived from `$bits`, and whose digest is `$output_bits` long,
+/// `$bits` unless given
+macro_rules! fixed_width_variant {
+    ($(#[$doc:meta])* $name:ident, $bits:expr) => {
+        fixed_width_variant!($(#[$doc])* $name, $bits, $bits);
+    };
+    ($(#[$doc:meta])* $name:ident, $bits:expr, $output_bits:expr) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name {
+            inner: Mirror256,
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl $name {
+            /// State size in bits
+            pub const BITS: usize = $bits;
+            /// Message block size in bytes
+            pub const BLOCK_SIZE: usize = $bits / 8;
+            /// Digest length in bytes
+            pub const OUTPUT_SIZE: usize = $output_bits / 8;
+
+            /// Create a hasher with the standard depth and initial state
+            pub fn new() -> Self {
+                let inner = Mirror256::builder()
+                    .size(Self::BITS)
+                    .output_size(8 * Self::OUTPUT_SIZE)
+                    .build()
+                    .expect("fixed-width parameters are valid");
+                $name { inner }
+            }
+
+            /// Create a hasher that has absorbed `m`
+            pub fn new_from_bytes(m: &[u8]) -> Self {
+                let mut hasher = Self::new();
+                hasher.update(m);
+                hasher
+            }
+
+            /// Update the hasher with new data
+            pub fn update(&mut self, m: &[u8]) {
+                self.inner.update(m);
+            }
+
+            /// Update the hasher with a string, hashing its UTF-8 bytes
+            pub fn update_str(&mut self, m: &str) {
+                self.inner.update_str(m);
+            }
+
+            /// Pad the buffered tail, process the final block(s) and return the digest bytes
+            pub fn finalize(self) -> Vec<u8> {
+                self.inner.finalize()
+            }
+
+            /// Pad and process the buffered tail, then return a reader of unlimited output
+            pub fn finalize_xof(self) -> XofReader {
+                self.inner.finalize_xof()
+            }
+
+            /// Get the digest of the data absorbed so far as a byte array
+            pub fn digest(&self) -> Vec<u8> {
+                self.inner.digest()
+            }
+
+            /// Get the digest as a hexadecimal string
+            pub fn hexdigest(&self) -> String {
+                self.inner.hexdigest()
+            }
+        }
+    };
+}
+
+fixed_width_variant!(
+    /// Mirror hash over a 128-bit state: 16-byte blocks and 16-byte digests
+    Mirror128,
+    128
+);
+
+fixed_width_variant!(
+    /// Mirror hash over a 224-bit state: 28-byte blocks and 28-byte digests
+    Mirror224,
+    224
+);
+
+fixed_width_variant!(
+    /// Mirror hash over a 384-bit state: 48-byte blocks and 48-byte digests
+    Mirror384,
+    384
+);
+
+fixed_width_variant!(
+    /// Mirror hash over a 512-bit state: 64-byte blocks and 64-byte digests
+    Mirror512,
+    512
+);
+
+fixed_width_variant!(
+    /// Mirror256 truncated to 160 bits: 32-byte blocks and 20-byte digests, with the
+    /// digest length mixed into the initial state
+    #[allow(non_camel_case_types)]
+    Mirror256_160,
+    256,
+    160
+);
+
+fixed_width_variant!(
+    /// Mirror256 truncated to 128 bits: 32-byte blocks and 16-byte digests, with the
+    /// digest length mixed into the initial state
+    #[allow(non_camel_case_types)]
+    Mirror256_128,
+    256,
+    128
+);
+
+fixed_width_variant!(
+    /// Mirror256 truncated to 64 bits: 32-byte blocks and 8-byte digests, with the
+    /// digest length mixed into the initial state
+    #[allow(non_camel_case_types)]
+    Mirror256_64,
+    256,
+    64
+);
@@ -0,0 +1,109 @@
+//! Extendable output
+//!
+//! Output is squeezed much as message blocks are absorbed. After the padded message,
+//! output block `k` comes from a counter block holding `k` as a 64-bit big-endian
+//! integer in its last 8 bytes and zeros elsewhere. The counter block is permuted in
+//! as many passes as it takes to run `2 * size` layers, like the output transformation
+//! of a wide-pipe hasher, since one pass leaves the output of nearby messages related
+//! (see the `block` module). It is then fed forward as in Miyaguchi–Preneel: XORed
+//! with the counter block and with the previous output block, or the digest for
+//! block 0. Like a block hash, each output block then becomes the layer 0 encoding of
+//! the next, so every block is permuted under different gates. Blocks are produced in
+//! order whatever the sizes of the reads, so shorter outputs are prefixes of longer
+//! ones. The stream does not start with the digest.
+
+use crate::permutation::{self, Width, MAX_WORDS};
+use crate::schedule::GateSchedule;
+use crate::Compression;
+use std::io;
+
+/// Reader of the unbounded output of `Mirror256::finalize_xof`
+#[derive(Clone, Debug)]
+pub struct XofReader {
+    width: Width,
+    schedule: GateSchedule,
+    counter: u64,
+    block: Vec<u8>,
+    position: usize,
+}
+
+impl XofReader {
+    /// Reader over the output of a finished schedule
+    pub(crate) fn new(width: Width, schedule: GateSchedule) -> Self {
+        XofReader {
+            width,
+            schedule,
+            counter: 0,
+            block: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Fill `out` with the next bytes of output
+    pub fn read(&mut self, out: &mut [u8]) {
+        let mut out = out;
+        while !out.is_empty() {
+            if self.position == self.block.len() {
+                self.block = self.output_block();
+                self.position = 0;
+            }
+            let take = (self.block.len() - self.position).min(out.len());
+            out[..take].copy_from_slice(&self.block[self.position..self.position + take]);
+            self.position += take;
+            out = &mut out[take..];
+        }
+    }
+
+    /// The next `len` bytes of output
+    pub fn squeeze(&mut self, len: usize) -> Vec<u8> {
+        let mut out = vec![0; len];
+        self.read(&mut out);
+        out
+    }
+
+    /// Permute the next counter block and chain its output into the schedule
+    fn output_block(&mut self) -> Vec<u8> {
+        let mut block = vec![0; self.width.bits() / 8];
+        let at = block.len() - 8;
+        block[at..].copy_from_slice(&self.counter.to_be_bytes());
+        self.counter = self.counter.wrapping_add(1);
+
+        let mut state = [0u64; MAX_WORDS];
+        permutation::unpack(self.width, &block, &mut state);
+        let input = state;
+        for _ in 0..(2 * self.width.bits()).div_ceil(self.schedule.depth()) {
+            permutation::permute(self.width, &mut state, self.schedule.layers());
+        }
+        Compression::MiyaguchiPreneel.feed_forward(&mut state, &input, self.schedule.latest().encoding());
+        self.schedule.push(self.width, &state);
+        permutation::pack(self.width, &state)
+    }
+}
+
+impl io::Read for XofReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        XofReader::read(self, buf);
+        Ok(buf.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Mirror256Builder;
+
+    #[test]
+    fn test_reading_in_pieces_matches_one_read() {
+        for size in [128, 256, 1024] {
+            let mut hasher = Mirror256Builder::new().depth(16).size(size).build().unwrap();
+            hasher.update(b"extendable output");
+            let whole = hasher.clone().finalize_xof().squeeze(500);
+
+            let mut reader = hasher.finalize_xof();
+            let mut pieces = Vec::new();
+            for len in [0, 1, 7, 31, 32, 33, 64, 100, 232] {
+                pieces.extend(reader.squeeze(len));
+            }
+            assert_eq!(pieces, whole);
+        }
+    }
+}
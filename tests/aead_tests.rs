@@ -0,0 +1,89 @@
+use mirror_hash::{Mirror256Error, MirrorAead};
+
+#[test]
+fn test_known_answers() {
+    let aead = MirrorAead::new(&(0..16).collect::<Vec<u8>>()).unwrap();
+    let nonce: Vec<u8> = (16..32).collect();
+    assert_eq!(hex::encode(aead.seal(&nonce, b"", b"").unwrap()), "f7de67c67f4f8e0fe4f835b9c995f00f");
+    assert_eq!(
+        hex::encode(aead.seal(&nonce, b"associated data", b"The quick brown fox jumps over the lazy dog").unwrap()),
+        "0895108fb46d3550a25fbab8e73fdb11d840161ff7d302dca355cf200e4c7da77440fb45ac2e5cc1662d12\
+         83c8d156f4f513ec0605be355c2bbb31"
+    );
+}
+
+#[test]
+fn test_open_inverts_seal() {
+    let aead = MirrorAead::new(&(0..16).collect::<Vec<u8>>()).unwrap();
+    let nonce: Vec<u8> = (16..32).collect();
+    for len in [0, 1, 13, 14, 15, 28, 29, 100] {
+        for ad_len in [0, 14, 31] {
+            let plaintext: Vec<u8> = (0..len).map(|i| (i * 7) as u8).collect();
+            let ad = vec![0xad; ad_len];
+            let sealed = aead.seal(&nonce, &ad, &plaintext).unwrap();
+            assert_eq!(sealed.len(), len + MirrorAead::TAG_BYTES);
+            assert_eq!(aead.open(&nonce, &ad, &sealed).unwrap(), plaintext, "length {} ad {}", len, ad_len);
+        }
+    }
+}
+
+#[test]
+fn test_any_tampering_is_detected() {
+    let aead = MirrorAead::new(&(0..16).collect::<Vec<u8>>()).unwrap();
+    let nonce: Vec<u8> = (16..32).collect();
+    let sealed = aead.seal(&nonce, b"header", b"attack at dawn, not at dusk").unwrap();
+
+    for i in 0..sealed.len() {
+        let mut tampered = sealed.clone();
+        tampered[i] ^= 0x01;
+        assert_eq!(aead.open(&nonce, b"header", &tampered), Err(Mirror256Error::MacMismatch), "byte {}", i);
+    }
+    for bit in 0..48 {
+        let mut ad = b"header".to_vec();
+        ad[bit / 8] ^= 1 << (bit % 8);
+        assert_eq!(aead.open(&nonce, &ad, &sealed), Err(Mirror256Error::MacMismatch), "ad bit {}", bit);
+    }
+    assert_eq!(aead.open(&nonce, b"", &sealed), Err(Mirror256Error::MacMismatch));
+    assert_eq!(aead.open(&nonce, b"header", &sealed[1..]), Err(Mirror256Error::MacMismatch));
+    assert_eq!(aead.open(&nonce, b"header", &sealed[..sealed.len() - 1]), Err(Mirror256Error::MacMismatch));
+
+    let mut other_nonce = nonce.clone();
+    other_nonce[15] ^= 0x80;
+    assert_eq!(aead.open(&other_nonce, b"header", &sealed), Err(Mirror256Error::MacMismatch));
+
+    let other_key = MirrorAead::new(&[0; 16]).unwrap();
+    assert_eq!(other_key.open(&nonce, b"header", &sealed), Err(Mirror256Error::MacMismatch));
+}
+
+#[test]
+fn test_associated_data_and_plaintext_are_separated() {
+    // Moving bytes between the associated data and the plaintext changes the tag
+    let aead = MirrorAead::new(&(0..16).collect::<Vec<u8>>()).unwrap();
+    let nonce: Vec<u8> = (16..32).collect();
+    let a = aead.seal(&nonce, b"ab", b"").unwrap();
+    let b = aead.seal(&nonce, b"a", b"b").unwrap();
+    let c = aead.seal(&nonce, b"", b"ab").unwrap();
+    assert_ne!(a[a.len() - 16..], b[b.len() - 16..]);
+    assert_ne!(b[b.len() - 16..], c[c.len() - 16..]);
+}
+
+#[test]
+fn test_nonces_give_different_ciphertexts() {
+    let aead = MirrorAead::new(&(0..16).collect::<Vec<u8>>()).unwrap();
+    let nonce: Vec<u8> = (16..32).collect();
+    let mut other_nonce = nonce.clone();
+    other_nonce[0] ^= 1;
+    assert_ne!(aead.seal(&nonce, b"", b"same message").unwrap(), aead.seal(&other_nonce, b"", b"same message").unwrap());
+}
+
+#[test]
+fn test_misuse_is_reported() {
+    assert_eq!(MirrorAead::new(&[0; 15]).err(), Some(Mirror256Error::KeyLength { len: 15, expected: 16 }));
+    assert_eq!(MirrorAead::new(&[0; 32]).err(), Some(Mirror256Error::KeyLength { len: 32, expected: 16 }));
+
+    let aead = MirrorAead::new(&(0..16).collect::<Vec<u8>>()).unwrap();
+    let nonce: Vec<u8> = (16..32).collect();
+    assert_eq!(aead.seal(&[0; 12], b"", b"msg"), Err(Mirror256Error::NonceLength { len: 12, expected: 16 }));
+    assert_eq!(aead.open(&[0; 17], b"", &[0; 20]), Err(Mirror256Error::NonceLength { len: 17, expected: 16 }));
+    assert_eq!(aead.open(&nonce, b"", &[0; 15]), Err(Mirror256Error::CiphertextTooShort { len: 15, min: 16 }));
+}
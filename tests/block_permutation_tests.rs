@@ -0,0 +1,97 @@
+mod common;
+
+use common::{depth16, depth16_builder};
+use mirror_hash::{BlockPermutation, Mirror256Error};
+
+fn block(len: usize, seed: u8) -> Vec<u8> {
+    (0..len).map(|i| (i as u8).wrapping_mul(31).wrapping_add(seed)).collect()
+}
+
+#[test]
+fn test_inverse_undoes_forward() {
+    for size in [128, 224, 256, 512, 1024] {
+        let permutation = depth16_builder().size(size).build().unwrap().block_permutation();
+        assert_eq!(permutation.size(), size);
+        assert_eq!(permutation.depth(), 16);
+
+        for seed in 0..8 {
+            let original = block(size / 8, seed);
+            let mut state = original.clone();
+            permutation.forward(&mut state);
+            assert_ne!(state, original);
+            permutation.inverse(&mut state);
+            assert_eq!(state, original, "size {}", size);
+
+            // And the other way round
+            permutation.inverse(&mut state);
+            permutation.forward(&mut state);
+            assert_eq!(state, original);
+        }
+    }
+}
+
+#[test]
+fn test_forward_output_chains_into_next_block() {
+    let mut hasher = depth16();
+    hasher.update(&block(64, 1));
+    let before = hasher.block_permutation();
+
+    let message = block(32, 2);
+    let mut output = message.clone();
+    before.forward(&mut output);
+
+    hasher.update(&message);
+    let after = hasher.block_permutation();
+    let (old, new) = (before.encodings(), after.encodings());
+    assert_eq!(new[0], output);
+    assert_eq!(new[1..], old[..15]);
+
+    // The chaining value and the earlier state give back the block
+    before.inverse(&mut output);
+    assert_eq!(output, message);
+}
+
+#[test]
+fn test_forward_of_padded_block_is_digest() {
+    let hasher = depth16();
+    let mut padded = b"abc".to_vec();
+    padded.push(0x80);
+    padded.resize(24, 0);
+    padded.extend_from_slice(&24u64.to_be_bytes());
+
+    let mut state = padded.clone();
+    hasher.block_permutation().forward(&mut state);
+    let mut single = hasher.clone();
+    single.update(b"abc");
+    assert_eq!(state, single.finalize());
+}
+
+#[test]
+fn test_new_from_encodings_matches_hasher() {
+    let permutation = depth16_builder().size(224).build().unwrap().block_permutation();
+    let encodings = permutation.encodings();
+    let slices: Vec<&[u8]> = encodings.iter().map(Vec::as_slice).collect();
+    let rebuilt = BlockPermutation::new(224, &slices).unwrap();
+
+    let (mut a, mut b) = (block(28, 3), block(28, 3));
+    permutation.forward(&mut a);
+    rebuilt.forward(&mut b);
+    assert_eq!(a, b);
+}
+
+#[test]
+fn test_new_rejects_invalid_encodings() {
+    let encoding = [0u8; 32];
+    assert_eq!(BlockPermutation::new(256, &[]).err(), Some(Mirror256Error::ZeroDepth));
+    assert_eq!(
+        BlockPermutation::new(256, &[&encoding, &encoding[..31]]).err(),
+        Some(Mirror256Error::EncodingLength { len: 31, expected: 32 })
+    );
+    assert_eq!(BlockPermutation::new(250, &[&encoding]).err(), Some(Mirror256Error::SizeNotMultipleOf8(250)));
+}
+
+#[test]
+#[should_panic(expected = "one block long")]
+fn test_forward_rejects_wrong_state_length() {
+    depth16().block_permutation().forward(&mut [0; 31]);
+}
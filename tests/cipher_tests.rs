@@ -0,0 +1,127 @@
+use mirror_hash::{Mirror256Error, MirrorCipher};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+fn random_bytes(rng: &mut StdRng, len: usize) -> Vec<u8> {
+    (0..len).map(|_| rng.gen()).collect()
+}
+
+#[test]
+fn test_known_answers() {
+    let cipher = MirrorCipher::new(&(0..32).collect::<Vec<u8>>()).unwrap();
+    let mut block = [0u8; 32];
+    cipher.encrypt_block(&mut block);
+    assert_eq!(hex::encode(block), "71f51f4f08ad96823fc371d25bc27a483ca1ddea7bd2d6967434b66bdae18526");
+
+    let mut block: Vec<u8> = (32..64).collect();
+    cipher.encrypt_block(&mut block);
+    assert_eq!(hex::encode(block), "0e8a182071a9afa9ba075c208aa2a1511bfb24eb4567df5a479e94b4b8c37c6b");
+
+    let message = b"The quick brown fox jumps over the lazy dog";
+    let nonce: Vec<u8> = (0..16).collect();
+    assert_eq!(
+        hex::encode(cipher.encrypt_ctr(&nonce, message).unwrap()),
+        "45d1df03157e0cdd54fd70ee9689d6621548f08b99020e2c92e0fd6bb69a6ca1ac976cf3c9b4ea117be58c"
+    );
+    assert_eq!(
+        hex::encode(cipher.encrypt_cbc(&[0x11; 32], message).unwrap()),
+        "1dff86f41784d239079364d534fe84cd4c069886fea9a6a56d928779066360fd\
+         bad9dca0feb0b366aea7dbd1bea21a10c3e699af7d9ada9e364f8dfd28521021"
+    );
+}
+
+#[test]
+fn test_block_round_trips_under_random_keys() {
+    let mut rng = StdRng::seed_from_u64(21);
+    for _ in 0..4 {
+        let cipher = MirrorCipher::new(&random_bytes(&mut rng, 32)).unwrap();
+        for _ in 0..4 {
+            let original = random_bytes(&mut rng, 32);
+            let mut block = original.clone();
+            cipher.encrypt_block(&mut block);
+            assert_ne!(block, original);
+            cipher.decrypt_block(&mut block);
+            assert_eq!(block, original);
+        }
+    }
+}
+
+#[test]
+fn test_modes_round_trip_under_random_keys_and_messages() {
+    let mut rng = StdRng::seed_from_u64(2021);
+    for _ in 0..4 {
+        let cipher = MirrorCipher::new(&random_bytes(&mut rng, 32)).unwrap();
+        let nonce = random_bytes(&mut rng, 16);
+        let iv = random_bytes(&mut rng, 32);
+        for len in [0, 1, 31, 32, 33, 64, rng.gen_range(0..200)] {
+            let message = random_bytes(&mut rng, len);
+
+            let ctr = cipher.encrypt_ctr(&nonce, &message).unwrap();
+            assert_eq!(ctr.len(), len);
+            assert_eq!(cipher.decrypt_ctr(&nonce, &ctr).unwrap(), message);
+
+            let cbc = cipher.encrypt_cbc(&iv, &message).unwrap();
+            assert_eq!(cbc.len(), (len / 32 + 1) * 32);
+            assert_eq!(cipher.decrypt_cbc(&iv, &cbc).unwrap(), message);
+        }
+    }
+}
+
+#[test]
+fn test_keys_give_different_ciphers() {
+    let mut key = [0u8; 32];
+    let mut first = [0u8; 32];
+    MirrorCipher::new(&key).unwrap().encrypt_block(&mut first);
+    key[31] = 1;
+    let mut second = [0u8; 32];
+    MirrorCipher::new(&key).unwrap().encrypt_block(&mut second);
+    assert_ne!(first, second);
+}
+
+#[test]
+fn test_one_bit_changes_about_half_the_block() {
+    let cipher = MirrorCipher::new(&(0..32).collect::<Vec<u8>>()).unwrap();
+    let mut base = [0u8; 32];
+    cipher.encrypt_block(&mut base);
+    for bit in [0, 7, 100, 255] {
+        let mut block = [0u8; 32];
+        block[bit / 8] ^= 1 << (bit % 8);
+        cipher.encrypt_block(&mut block);
+        let flipped: u32 = block.iter().zip(&base).map(|(a, b)| (a ^ b).count_ones()).sum();
+        assert!((80..=176).contains(&flipped), "bit {} flipped {} bits", bit, flipped);
+    }
+}
+
+#[test]
+fn test_cbc_rejects_bad_ciphertexts() {
+    let cipher = MirrorCipher::new(&(0..32).collect::<Vec<u8>>()).unwrap();
+    let iv = [0u8; 32];
+    assert_eq!(cipher.decrypt_cbc(&iv, &[]), Err(Mirror256Error::CiphertextLength { len: 0, block: 32 }));
+    assert_eq!(cipher.decrypt_cbc(&iv, &[0; 33]), Err(Mirror256Error::CiphertextLength { len: 33, block: 32 }));
+
+    // A final block that decrypts to a zero padding byte, and one with a padding run
+    // broken in the middle
+    for plain in [[0u8; 32], {
+        let mut block = [4u8; 32];
+        block[29] = 5;
+        block
+    }] {
+        let mut block = plain;
+        cipher.encrypt_block(&mut block);
+        assert_eq!(cipher.decrypt_cbc(&iv, &block), Err(Mirror256Error::InvalidPadding));
+    }
+
+    // A full block of padding is valid
+    let mut block = [32u8; 32];
+    cipher.encrypt_block(&mut block);
+    assert_eq!(cipher.decrypt_cbc(&iv, &block).unwrap(), b"");
+}
+
+#[test]
+fn test_lengths_are_checked() {
+    assert_eq!(MirrorCipher::new(&[0; 16]).err(), Some(Mirror256Error::KeyLength { len: 16, expected: 32 }));
+    let cipher = MirrorCipher::new(&(0..32).collect::<Vec<u8>>()).unwrap();
+    assert_eq!(cipher.encrypt_ctr(&[0; 12], b"x"), Err(Mirror256Error::NonceLength { len: 12, expected: 16 }));
+    assert_eq!(cipher.encrypt_cbc(&[0; 16], b"x"), Err(Mirror256Error::IvLength { len: 16, expected: 32 }));
+    assert_eq!(cipher.decrypt_cbc(&[0; 31], &[0; 32]), Err(Mirror256Error::IvLength { len: 31, expected: 32 }));
+}
@@ -0,0 +1,16 @@
+//! Fixtures shared by the integration tests
+
+// Each test crate uses a different subset
+#![allow(dead_code)]
+
+use mirror_hash::{Mirror256, Mirror256Builder};
+
+/// Builder for 16-layer hashers, which keep the tests fast
+pub fn depth16_builder() -> Mirror256Builder {
+    Mirror256::builder().depth(16)
+}
+
+/// A 16-layer hasher over the standard 256-bit state
+pub fn depth16() -> Mirror256 {
+    depth16_builder().build().unwrap()
+}
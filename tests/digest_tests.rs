@@ -0,0 +1,59 @@
+#![cfg(feature = "digest")]
+
+use digest::dev::{fixed_reset_test, fixed_test};
+use hkdf::SimpleHkdf;
+use hmac::{Mac, SimpleHmac};
+use mirror_hash::digest::core_api::BlockSizeUser;
+use mirror_hash::digest::Digest;
+use mirror_hash::{hkdf as mirror_hkdf, HmacMirror256, Mirror256, Mirror256Core};
+
+#[test]
+fn test_harness_known_answers() {
+    let cases: [(&[u8], &str); 2] = [
+        (b"", "1707040180b949c17c424a7cd727577b21592e52aa5f14e799a6bd427b82a2bb"),
+        (b"abc", "dfca8abe826c542163124a7cd727577b21592e52aa5f14e799a6be254e8b134e"),
+    ];
+    for (input, output) in cases {
+        let output = hex::decode(output).unwrap();
+        assert_eq!(fixed_reset_test::<Mirror256Core>(input, &output), None, "{:?}", input);
+        assert_eq!(fixed_test::<Mirror256Core>(input, &output), None, "{:?}", input);
+    }
+}
+
+#[test]
+fn test_harness_matches_native_hashing() {
+    for len in [1, 31, 32, 33, 64, 100, 1000] {
+        let input: Vec<u8> = (0..len).map(|i| (i * 13) as u8).collect();
+        let output = Mirror256::new_from_bytes(&input).finalize();
+        assert_eq!(fixed_reset_test::<Mirror256Core>(&input, &output), None, "length {}", len);
+        assert_eq!(fixed_test::<Mirror256Core>(&input, &output), None, "length {}", len);
+    }
+}
+
+#[test]
+fn test_sizes() {
+    assert_eq!(<Mirror256Core as Digest>::output_size(), 32);
+    assert_eq!(<Mirror256Core as BlockSizeUser>::block_size(), Mirror256::new().block_size());
+}
+
+#[test]
+fn test_generic_hmac_matches_hmac_mirror256() {
+    // Both pad keys to the 32-byte block, so the tags agree
+    for key in [&b"key"[..], &[0x0b; 32], &[0xaa; 131]] {
+        let mut generic = SimpleHmac::<Mirror256Core>::new_from_slice(key).unwrap();
+        Mac::update(&mut generic, b"message");
+        let mut native = HmacMirror256::new_from_slice(key).unwrap();
+        native.update(b"message");
+        assert_eq!(generic.finalize().into_bytes()[..], native.finalize()[..], "key length {}", key.len());
+    }
+}
+
+#[test]
+fn test_generic_hkdf_matches_native_hkdf() {
+    let (prk, hkdf) = SimpleHkdf::<Mirror256Core>::extract(Some(b"salt"), b"input keying material");
+    assert_eq!(prk[..], mirror_hkdf::extract(b"salt", b"input keying material")[..]);
+
+    let mut okm = [0; 80];
+    hkdf.expand(b"info", &mut okm).unwrap();
+    assert_eq!(okm[..], mirror_hkdf::expand(&prk, b"info", 80).unwrap()[..]);
+}
@@ -0,0 +1,98 @@
+use mirror_hash::{hkdf, HmacMirror256, Hkdf, Mirror256, Mirror256Error};
+
+fn range(from: u8, to: u8) -> Vec<u8> {
+    (from..=to).collect()
+}
+
+/// RFC 5869 test cases 1 to 3 with Mirror256: IKM, salt, info, L, PRK and OKM
+#[test]
+fn test_known_answers() {
+    let cases = [
+        (
+            vec![0x0b; 22],
+            range(0x00, 0x0c),
+            range(0xf0, 0xf9),
+            42,
+            "5d06e70fea4a95b4035d7943a5a243d27fbf5570bd0ea67ff3e785b138de370a",
+            "0facd3c4000f0d9690e1185cb5fcf5a78d8878fdd412557af2b36f0ecad5f4949bf77c6d13dd11a32cc2",
+        ),
+        (
+            range(0x00, 0x4f),
+            range(0x60, 0xaf),
+            range(0xb0, 0xff),
+            82,
+            "276135ae69dacc2e32c66eb3949a8080d31cb27be1b3ff885a5850fbdc3a6566",
+            "be45c2fcb6ae1455e8a6c861cc8882b7e072a2b8b54a28cc06ac6ba5a98d8161fa3a9e34b7ca13fa5c75978e972685a7\
+             c97c9d4bf27b80e788a789a7400143fae495f6d9bad89d0b40e9e797607dc3facdef",
+        ),
+        (
+            vec![0x0b; 22],
+            vec![],
+            vec![],
+            42,
+            "e75e330387f59e8b67ea1836e43609a230741cbdd26d2d04bb79e3289f7859b7",
+            "66250026959c7c75bd2ebdba6057e732c2d61ea5b4af97f1c58537ef6c750b0bde1c5bccbd6c01c420d8",
+        ),
+    ];
+
+    for (ikm, salt, info, length, prk, okm) in cases {
+        let extracted = hkdf::extract(&salt, &ikm);
+        assert_eq!(hex::encode(&extracted), prk);
+        assert_eq!(hex::encode(hkdf::expand(&extracted, &info, length).unwrap()), okm);
+        assert_eq!(hex::encode(Hkdf::default().derive(&salt, &ikm, &info, length).unwrap()), okm);
+    }
+}
+
+#[test]
+fn test_expand_follows_rfc5869() {
+    let hmac = |key: &[u8], parts: &[&[u8]]| {
+        let mut mac = HmacMirror256::new_from_slice(key).unwrap();
+        for part in parts {
+            mac.update(part);
+        }
+        mac.finalize()
+    };
+
+    let prk = hkdf::extract(b"salt", b"input keying material");
+    assert_eq!(prk, hmac(b"salt", &[b"input keying material"]));
+    assert_eq!(hkdf::extract(b"", b"ikm"), hkdf::extract(&[0; 32], b"ikm"));
+
+    let t1 = hmac(&prk, &[b"info", &[1]]);
+    let t2 = hmac(&prk, &[&t1, b"info", &[2]]);
+    let t3 = hmac(&prk, &[&t2, b"info", &[3]]);
+    assert_eq!(hkdf::expand(&prk, b"info", 80).unwrap(), [t1, t2, t3[..16].to_vec()].concat());
+
+    // Shorter outputs are prefixes of longer ones
+    let long = hkdf::expand(&prk, b"info", 100).unwrap();
+    assert_eq!(hkdf::expand(&prk, b"info", 33).unwrap(), long[..33]);
+    assert!(hkdf::expand(&prk, b"info", 0).unwrap().is_empty());
+}
+
+#[test]
+fn test_output_limits() {
+    let prk = hkdf::extract(b"salt", b"ikm");
+    assert_eq!(hkdf::expand(&prk, b"", 255 * 32).unwrap().len(), 255 * 32);
+    assert_eq!(
+        hkdf::expand(&prk, b"", 255 * 32 + 1).err(),
+        Some(Mirror256Error::OutputTooLong { len: 255 * 32 + 1, max: 255 * 32 })
+    );
+    assert_eq!(
+        hkdf::expand(&prk[..31], b"", 32).err(),
+        Some(Mirror256Error::PseudoRandomKeyTooShort { len: 31, min: 32 })
+    );
+}
+
+#[test]
+fn test_digest_length_follows_hasher() {
+    let hkdf = Hkdf::with_hasher(&Mirror256::builder().size(512).depth(16).build().unwrap());
+    assert_eq!(hkdf.output_size(), 64);
+    assert_eq!(hkdf.max_output(), 255 * 64);
+
+    let prk = hkdf.extract(b"salt", b"ikm");
+    assert_eq!(prk.len(), 64);
+    assert_eq!(hkdf.expand(&prk, b"info", 100).unwrap().len(), 100);
+    assert_eq!(
+        hkdf.expand(&prk[..32], b"info", 10).err(),
+        Some(Mirror256Error::PseudoRandomKeyTooShort { len: 32, min: 64 })
+    );
+}
@@ -0,0 +1,146 @@
+use mirror_hash::{HmacMirror256, Mirror256, Mirror256Error};
+
+/// Keys and messages of the RFC 4231 HMAC test cases 1 to 4 and 6
+fn rfc4231_cases() -> Vec<(Vec<u8>, Vec<u8>)> {
+    vec![
+        (vec![0x0b; 20], b"Hi There".to_vec()),
+        (b"Jefe".to_vec(), b"what do ya want for nothing?".to_vec()),
+        (vec![0xaa; 20], vec![0xdd; 50]),
+        ((0x01..=0x19).collect(), vec![0xcd; 50]),
+        (vec![0xaa; 131], b"Test Using Larger Than Block-Size Key - Hash Key First".to_vec()),
+    ]
+}
+
+fn hmac(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = HmacMirror256::new_from_slice(key).unwrap();
+    mac.update(message);
+    mac.finalize()
+}
+
+fn keyed(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut hasher = Mirror256::builder().key(key).build().unwrap();
+    hasher.update(message);
+    hasher.finalize()
+}
+
+#[test]
+fn test_hmac_test_vectors() {
+    let expected = [
+        "5c0de1e79235564efdb84fb2bbec2ef6f4d973f575c833156957c961800673c6",
+        "86dcc7522610e60a9ba5082ca5085200329bed8691c3fbce566d860b8510e3b1",
+        "b25b7506e51fc0081cc729d9f1f151aa6e1e5d604a9353bc6a4034469d665c3c",
+        "b701fc2e8df0c20a9ce2a9b10fafb39aea1a0f5a392ba426da030bdd4e5ed291",
+        "ff0d3c510b028a65ccb4542d0a79e1e71813171a539f8727aa43ec4e59fa1812",
+    ];
+    for ((key, message), expected) in rfc4231_cases().iter().zip(expected) {
+        assert_eq!(hex::encode(hmac(key, message)), expected);
+    }
+}
+
+#[test]
+fn test_hmac_follows_rfc2104() {
+    let hash = |parts: &[&[u8]]| {
+        let mut hasher = Mirror256::new();
+        for part in parts {
+            hasher.update(part);
+        }
+        hasher.finalize()
+    };
+
+    for (key, message) in rfc4231_cases() {
+        let mut key_block = if key.len() > 32 { hash(&[&key]) } else { key.clone() };
+        key_block.resize(32, 0);
+        let ipad: Vec<u8> = key_block.iter().map(|k| k ^ 0x36).collect();
+        let opad: Vec<u8> = key_block.iter().map(|k| k ^ 0x5c).collect();
+
+        let expected = hash(&[&opad, &hash(&[&ipad, &message])]);
+        assert_eq!(hmac(&key, &message), expected);
+    }
+}
+
+#[test]
+fn test_hmac_streams_and_verifies() {
+    let message = vec![0x5a; 200];
+    let tag = hmac(b"key", &message);
+
+    let mut mac = HmacMirror256::new_from_slice(b"key").unwrap();
+    for chunk in message.chunks(13) {
+        mac.update(chunk);
+    }
+    assert!(mac.clone().verify(&tag).is_ok());
+
+    let mut wrong = tag.clone();
+    wrong[31] ^= 1;
+    assert_eq!(mac.clone().verify(&wrong), Err(Mirror256Error::MacMismatch));
+    assert_eq!(mac.verify(&tag[..16]), Err(Mirror256Error::MacMismatch));
+}
+
+#[test]
+fn test_hmac_over_configured_hasher() {
+    let hasher = Mirror256::builder().size(512).build().unwrap();
+    let mut mac = HmacMirror256::with_hasher(&hasher, &[7; 100]).unwrap();
+    mac.update(b"message");
+    let tag = mac.finalize();
+    assert_eq!(tag.len(), 64);
+    assert_ne!(tag[..32], hmac(&[7; 100], b"message")[..]);
+}
+
+#[test]
+fn test_keyed_test_vectors() {
+    let expected = [
+        "6e9e3ce691d6ba3d78680ade955581a149e872675002d5a38c50fcbf00d3df9f",
+        "1be08198990618cf2bc7d1dd7b9b5a5c24a0ee1d3ffbc44ee40a5b6080b84896",
+        "7379b357f973fe9f741feaefd7e4c0d23c3794bf0ed119149b3e6c0347dce987",
+        "aecdde792fb852f8fba2e78a819f52cddb3e50cb3f5f1cebaf69ce6fd79ecec1",
+        "a25a35baecc1d86508da9218bfb0a420ff59929ab4f9e6116e46f219925a9053",
+    ];
+    for ((key, message), expected) in rfc4231_cases().iter().zip(expected) {
+        assert_eq!(hex::encode(keyed(key, message)), expected);
+    }
+}
+
+#[test]
+fn test_keyed_mode_derives_every_layer_from_the_key() {
+    let key = b"layer key";
+    let unkeyed = Mirror256::builder().depth(16).personalization(b"app").build().unwrap();
+    let mut encodings: Vec<Vec<u8>> = Vec::new();
+    for layer in 0..16u32 {
+        let mut hasher = unkeyed.clone();
+        hasher.update(b"Mirror256 keyed layer");
+        hasher.update(&layer.to_be_bytes());
+        hasher.update(key);
+        hasher.update(encodings.last().map_or(&[][..], Vec::as_slice));
+        encodings.push(hasher.finalize());
+    }
+
+    let hasher = Mirror256::builder().depth(16).personalization(b"app").key(key).build().unwrap();
+    assert_eq!(hasher.block_permutation().encodings(), encodings);
+
+    // Neighbouring layers are unrelated; near-equal ones would mostly cancel
+    for pair in encodings.windows(2) {
+        let distance: u32 = pair[0].iter().zip(&pair[1]).map(|(a, b)| (a ^ b).count_ones()).sum();
+        assert!(distance > 80, "layers {} bits apart", distance);
+    }
+
+    assert_ne!(keyed(b"one", b"m"), keyed(b"two", b"m"));
+    assert_ne!(keyed(b"one", b"m"), Mirror256::new_from_bytes(b"m").finalize());
+    assert_eq!(keyed(b"", b"m"), Mirror256::new_from_bytes(b"m").finalize());
+}
+
+#[test]
+fn test_keyed_digests_of_similar_messages_are_unrelated() {
+    for i in 0..20 {
+        let a = keyed(b"key", format!("message {}", i).as_bytes());
+        let b = keyed(b"key", format!("message {}", i + 1).as_bytes());
+        let distance: u32 = a.iter().zip(&b).map(|(x, y)| (x ^ y).count_ones()).sum();
+        assert!((80..=176).contains(&distance), "digests {} bits apart after message {}", distance, i);
+    }
+}
+
+#[test]
+fn test_key_stays_out_of_debug_output() {
+    let builder = Mirror256::builder().key(b"hunter2");
+    let debug = format!("{:?}", builder);
+    assert!(debug.contains("Key(7 bytes)"));
+    assert!(!debug.contains(&format!("{:?}", b"hunter2".to_vec())));
+}
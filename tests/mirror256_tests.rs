@@ -1,8 +1,20 @@
-use mirror_hash::Mirror256;
+mod common;
+
+use common::{depth16, depth16_builder};
+use mirror_hash::{
+    BlockPermutation, Compression, InitialState, KeySchedule, Mirror256, Mirror256Builder, Mirror256Error, Padding, Preset,
+    DEFAULT_SEED, MAX_DEPTH, MAX_SIZE, MIN_OUTPUT_SIZE, MIN_SIZE, PERSONALIZATION_BYTES, SALT_BYTES,
+};
+
+fn depth16_digest(input: &[u8]) -> String {
+    let mut hasher = depth16();
+    hasher.update(input);
+    hasher.hexdigest()
+}
 
 #[test]
 fn test_empty_string() {
-    let hasher = Mirror256::new(Some(""), None, None, true);
+    let hasher = mirror_hash::new(Some(""));
     let digest = hasher.hexdigest();
     assert!(!digest.is_empty());
     println!("Empty string hash: {}", digest);
@@ -13,10 +25,10 @@ fn test_consistency() {
     // The same input should always produce the same hash
     let input = "Test message for consistency";
     
-    let hasher1 = Mirror256::new(Some(input), None, None, true);
+    let hasher1 = mirror_hash::new(Some(input));
     let digest1 = hasher1.hexdigest();
     
-    let hasher2 = Mirror256::new(Some(input), None, None, true);
+    let hasher2 = mirror_hash::new(Some(input));
     let digest2 = hasher2.hexdigest();
     
     assert_eq!(digest1, digest2);
@@ -28,10 +40,10 @@ fn test_different_inputs() {
     let input1 = "First message";
     let input2 = "Second message";
     
-    let hasher1 = Mirror256::new(Some(input1), None, None, true);
+    let hasher1 = mirror_hash::new(Some(input1));
     let digest1 = hasher1.hexdigest();
     
-    let hasher2 = Mirror256::new(Some(input2), None, None, true);
+    let hasher2 = mirror_hash::new(Some(input2));
     let digest2 = hasher2.hexdigest();
     
     assert_ne!(digest1, digest2);
@@ -39,28 +51,47 @@ fn test_different_inputs() {
 
 #[test]
 fn test_incremental_updates() {
-    // Due to the way the hasher works with 32-byte chunks, we need to ensure
-    // our test matches the expected behavior
-    
-    // Basic test with a simple string
     let input = "Hello, world!";
     
     // Single update
-    let hasher1 = Mirror256::new(Some(input), None, None, true);
+    let hasher1 = mirror_hash::new(Some(input));
     let digest1 = hasher1.hexdigest();
     
     // Incremental updates with same content
-    let mut hasher2 = Mirror256::new(None, None, None, true);
-    hasher2.update("Hello, ");
-    hasher2.update("world!");
+    let mut hasher2 = Mirror256::new();
+    hasher2.update_str("Hello, ");
+    hasher2.update_str("world!");
     let digest2 = hasher2.hexdigest();
     
-    println!("Single update digest: {}", digest1);
-    println!("Incremental update digest: {}", digest2);
+    assert_eq!(digest1, digest2);
+}
+
+#[test]
+fn test_every_split_point() {
+    // Any split of the input across two updates yields the one-shot digest
+    let input: Vec<u8> = (0..100u8).collect();
+    let expected = depth16_digest(&input);
     
-    // This may not match due to how the hasher processes chunks, but we'll verify the digests are non-empty
-    assert!(!digest1.is_empty());
-    assert!(!digest2.is_empty());
+    for split in 0..=input.len() {
+        let mut hasher = depth16();
+        hasher.update(&input[..split]);
+        hasher.update(&input[split..]);
+        assert_eq!(hasher.hexdigest(), expected, "split at {}", split);
+    }
+}
+
+#[test]
+fn test_byte_at_a_time_and_interleaved_digests() {
+    let input: Vec<u8> = (0..75u8).map(|b| b.wrapping_mul(37)).collect();
+    let expected = depth16_digest(&input);
+    
+    // Feed one byte at a time, taking intermediate digests along the way
+    let mut hasher = depth16();
+    for (i, b) in input.iter().enumerate() {
+        hasher.update(std::slice::from_ref(b));
+        assert_eq!(hasher.hexdigest(), depth16_digest(&input[..=i]));
+    }
+    assert_eq!(hasher.hexdigest(), expected);
 }
 
 #[test]
@@ -71,7 +102,7 @@ fn test_long_input() {
         long_input.push_str(&format!("Chunk#{} ", i));
     }
     
-    let hasher = Mirror256::new(Some(&long_input), None, None, true);
+    let hasher = mirror_hash::new(Some(&long_input));
     let digest = hasher.hexdigest();
     assert!(!digest.is_empty());
 }
@@ -81,10 +112,11 @@ fn test_standard_vs_random_state() {
     // Standard state and random state should produce different hashes
     let input = "Test message for state comparison";
     
-    let hasher1 = Mirror256::new(Some(input), None, None, true);  // Standard state
+    let hasher1 = mirror_hash::new(Some(input));  // Standard state
     let digest1 = hasher1.hexdigest();
     
-    let hasher2 = Mirror256::new(Some(input), None, None, false); // Random state
+    let mut hasher2 = Mirror256::builder().initial_state(InitialState::Seeded(DEFAULT_SEED)).build().unwrap(); // Random state
+    hasher2.update_str(input);
     let digest2 = hasher2.hexdigest();
     
     // We may want to handle the case where they might be equal by chance, but it's unlikely
@@ -100,7 +132,7 @@ fn test_canary() {
     // Test with the canary message
     let input = "This is the canary.";
     
-    let hasher = Mirror256::new(Some(input), None, None, true);
+    let hasher = mirror_hash::new(Some(input));
     let digest = hasher.hexdigest();
     assert!(!digest.is_empty());
     println!("Canary message hash: {}", digest);
@@ -111,10 +143,10 @@ fn test_depth_parameter() {
     // Create two hashers with significantly different depths to ensure different outputs
     let input = "Test message for depth parameter";
     
-    let hasher1 = Mirror256::new(Some(input), Some(16), None, true);  // Depth 16
-    let digest1 = hasher1.hexdigest();
+    let digest1 = depth16_digest(input.as_bytes());
     
-    let hasher2 = Mirror256::new(Some(input), Some(128), None, true); // Depth 128
+    let mut hasher2 = Mirror256::builder().depth(128).build().unwrap();
+    hasher2.update_str(input);
     let digest2 = hasher2.hexdigest();
     
     // Print the digests to help debug
@@ -132,7 +164,7 @@ fn test_special_characters() {
     // Test with special characters
     let input = "!@#$%^&*()_+-=[]{}|;':\",./<>?";
     
-    let hasher = Mirror256::new(Some(input), None, None, true);
+    let hasher = mirror_hash::new(Some(input));
     let digest = hasher.hexdigest();
     assert!(!digest.is_empty());
 }
@@ -142,15 +174,523 @@ fn test_unicode_characters() {
     // Test with ASCII characters instead of Unicode to avoid encoding issues
     let input = "Hello World! This is a test with ASCII characters.";
     
-    let hasher = Mirror256::new(Some(input), None, None, true);
+    let hasher = mirror_hash::new(Some(input));
     let digest = hasher.hexdigest();
     assert!(!digest.is_empty());
     println!("ASCII test hash: {}", digest);
     
     // If we want to test Unicode safely:
     let safe_input = "こんにちは"; // Just a short Unicode string
-    let hasher2 = Mirror256::new(Some(safe_input), None, None, true);
+    let hasher2 = mirror_hash::new(Some(safe_input));
     let digest2 = hasher2.hexdigest();
     assert!(!digest2.is_empty());
     println!("Unicode test hash: {}", digest2);
-} 
\ No newline at end of file
+} 
+
+#[test]
+fn test_str_and_bytes_agree() {
+    let input = "Strings are hashed as their UTF-8 bytes";
+    
+    let hasher1 = mirror_hash::new(Some(input));
+    let hasher2 = Mirror256::new_from_bytes(input.as_bytes());
+    
+    assert_eq!(hasher1.hexdigest(), hasher2.hexdigest());
+}
+
+#[test]
+fn test_binary_input() {
+    // Chunks that are not valid UTF-8 must be hashed as they are, not substituted
+    let input1: Vec<u8> = (0..40).map(|i| 0x80 | i as u8).collect();
+    let input2: Vec<u8> = (0..40).map(|i| 0xC0 | i as u8).collect();
+    
+    let hasher1 = Mirror256::new_from_bytes(&input1);
+    let hasher2 = Mirror256::new_from_bytes(&input2);
+    
+    assert_ne!(hasher1.hexdigest(), hasher2.hexdigest());
+}
+
+#[test]
+fn test_multibyte_character_across_chunk_boundary() {
+    // The 'é' straddles the first 32-byte chunk boundary
+    let input1 = format!("{}é tail", "a".repeat(31));
+    let input2 = format!("{}è tail", "a".repeat(31));
+    
+    let hasher1 = mirror_hash::new(Some(&input1));
+    let hasher2 = mirror_hash::new(Some(&input2));
+    
+    assert_ne!(hasher1.hexdigest(), hasher2.hexdigest());
+}
+
+#[test]
+fn test_no_all_zero_digests() {
+    let zero = format!("0x{}", "0".repeat(64));
+    
+    // The empty message and block-aligned messages still get a padded final block
+    for input in ["", "0123456789abcdef0123456789abcdef", &"x".repeat(64)] {
+        let hasher = mirror_hash::new(Some(input));
+        assert_ne!(hasher.hexdigest(), zero, "all-zero digest for {:?}", input);
+    }
+}
+
+#[test]
+fn test_finalize_matches_digest() {
+    let hasher = mirror_hash::new(Some("finalize me"));
+    let digest = hasher.digest();
+    
+    assert_eq!(hasher.finalize(), digest);
+}
+
+#[test]
+fn test_length_strengthening() {
+    // Trailing zero bytes and bytes that look like padding change the digest
+    let inputs: [&[u8]; 4] = [b"abc", b"abc\0", b"abc\x80", b"abcA"];
+    let digests: Vec<String> = inputs
+        .iter()
+        .map(|input| Mirror256::new_from_bytes(input).hexdigest())
+        .collect();
+    
+    for i in 0..digests.len() {
+        for j in i + 1..digests.len() {
+            assert_ne!(digests[i], digests[j]);
+        }
+    }
+}
+
+#[test]
+fn test_legacy_padding() {
+    let legacy = Mirror256::builder().padding(Padding::Legacy);
+    
+    let mut hasher1 = legacy.build().unwrap();
+    hasher1.update(b"abc");
+    
+    let mut hasher2 = legacy.build().unwrap();
+    hasher2.update(b"abcA");
+    
+    // The legacy fill is ambiguous, which is why it is not the default
+    assert_eq!(hasher1.hexdigest(), hasher2.hexdigest());
+    assert_ne!(hasher1.hexdigest(), mirror_hash::new(Some("abc")).hexdigest());
+}
+
+#[test]
+fn test_builder_defaults_match_new() {
+    let mut hasher = Mirror256Builder::new().build().unwrap();
+    hasher.update(b"defaults");
+    
+    assert_eq!(hasher.hexdigest(), Mirror256::new_from_bytes(b"defaults").hexdigest());
+    assert_eq!(mirror_hash::new(Some("defaults")).hexdigest(), hasher.hexdigest());
+}
+
+#[test]
+fn test_builder_rejects_invalid_parameters() {
+    assert_eq!(Mirror256::builder().depth(0).build().err(), Some(Mirror256Error::ZeroDepth));
+    assert_eq!(
+        Mirror256::builder().depth(MAX_DEPTH + 1).build().err(),
+        Some(Mirror256Error::DepthTooLarge { depth: MAX_DEPTH + 1, max: MAX_DEPTH })
+    );
+    assert_eq!(Mirror256::builder().size(260).build().err(), Some(Mirror256Error::SizeNotMultipleOf8(260)));
+    assert_eq!(
+        Mirror256::builder().size(64).build().err(),
+        Some(Mirror256Error::SizeTooSmall { size: 64, min: MIN_SIZE })
+    );
+    assert_eq!(
+        Mirror256::builder().size(MAX_SIZE + 8).build().err(),
+        Some(Mirror256Error::SizeTooLarge { size: MAX_SIZE + 8, max: MAX_SIZE })
+    );
+    assert_eq!(
+        Mirror256::builder().personalization(&[1; 17]).build().err(),
+        Some(Mirror256Error::PersonalizationTooLong { len: 17, max: PERSONALIZATION_BYTES })
+    );
+    // A truncated digest length takes the last 8 bytes of the 16-byte parameter block
+    assert_eq!(
+        Mirror256::builder().size(128).output_size(64).personalization(&[1; 9]).build().err(),
+        Some(Mirror256Error::PersonalizationTooLong { len: 9, max: 8 })
+    );
+    assert!(Mirror256::builder().size(128).output_size(64).personalization(&[1; 8]).build().is_ok());
+    assert!(Mirror256::builder().size(128).personalization(&[1; 16]).build().is_ok());
+}
+
+#[test]
+fn test_presets() {
+    let digests: Vec<String> = [Preset::Standard, Preset::Fast, Preset::Conservative]
+        .iter()
+        .map(|&preset| {
+            let mut hasher = Mirror256Builder::preset(preset).build().unwrap();
+            hasher.update(b"preset");
+            hasher.hexdigest()
+        })
+        .collect();
+    
+    assert_eq!(digests[0], Mirror256::new_from_bytes(b"preset").hexdigest());
+    assert_ne!(digests[0], digests[1]);
+    assert_ne!(digests[0], digests[2]);
+}
+
+#[test]
+fn test_personalization() {
+    let digest = |personalization: &[u8]| {
+        let mut hasher = Mirror256::builder().personalization(personalization).build().unwrap();
+        hasher.update(b"same message");
+        hasher.hexdigest()
+    };
+    
+    assert_eq!(digest(b""), Mirror256::new_from_bytes(b"same message").hexdigest());
+    assert_ne!(digest(b"app one"), digest(b""));
+    assert_ne!(digest(b"app one"), digest(b"app two"));
+}
+
+#[test]
+fn test_salt_separates_digests() {
+    let digest = |personalization: &[u8], salt: &[u8]| {
+        let mut hasher = depth16_builder().personalization(personalization).salt(salt).build().unwrap();
+        hasher.update(b"same message");
+        hasher.hexdigest()
+    };
+
+    assert_eq!(digest(b"", b""), depth16_digest(b"same message"));
+    assert_ne!(digest(b"", b"salt one"), digest(b"", b""));
+    assert_ne!(digest(b"", b"salt one"), digest(b"", b"salt two"));
+    assert_ne!(digest(b"app", b"salt"), digest(b"salt", b"app"));
+    assert_ne!(digest(b"app", b"salt"), digest(b"app", b""));
+}
+
+#[test]
+fn test_salt_is_checked() {
+    assert_eq!(
+        Mirror256::builder().salt(&[1; 17]).build().err(),
+        Some(Mirror256Error::SaltTooLong { len: 17, max: SALT_BYTES })
+    );
+    assert_eq!(Mirror256::builder().depth(1).salt(b"s").build().err(), Some(Mirror256Error::SaltNeedsTwoLayers));
+}
+
+#[test]
+fn test_initial_value_reproduces_hasher() {
+    // A hasher's own encodings reproduce it
+    let encodings = depth16().block_permutation().encodings();
+    let mut explicit = Mirror256::builder().initial_value(&encodings).build().unwrap();
+    assert_eq!(explicit.depth(), 16);
+    explicit.update(b"message");
+    assert_eq!(explicit.hexdigest(), depth16_digest(b"message"));
+
+    let mut changed = encodings.clone();
+    changed[7][3] ^= 1;
+    let mut other = Mirror256::builder().initial_value(&changed).build().unwrap();
+    other.update(b"message");
+    assert_ne!(other.hexdigest(), explicit.hexdigest());
+}
+
+#[test]
+fn test_initial_value_absorbs_parameters() {
+    let encodings = depth16().block_permutation().encodings();
+    let salted = Mirror256::builder().initial_value(&encodings).salt(b"salt").build().unwrap();
+    assert_eq!(salted.hexdigest(), depth16_builder().salt(b"salt").build().unwrap().hexdigest());
+}
+
+#[test]
+fn test_initial_value_is_checked() {
+    let encodings = depth16().block_permutation().encodings();
+    let short: Vec<&[u8]> = vec![&[0; 32], &[0; 31]];
+    assert_eq!(
+        Mirror256::builder().initial_value(&short).build().err(),
+        Some(Mirror256Error::EncodingLength { len: 31, expected: 32 })
+    );
+    assert_eq!(
+        Mirror256::builder().initial_value(&encodings).depth(8).build().err(),
+        Some(Mirror256Error::InitialValueDepth { layers: 16, depth: 8 })
+    );
+    assert_eq!(Mirror256::builder().initial_value::<&[u8]>(&[]).build().err(), Some(Mirror256Error::ZeroDepth));
+}
+
+#[test]
+fn test_hash_many_matches_single_hashes() {
+    let records: Vec<Vec<u8>> = (0..37).map(|i| vec![i as u8; i * 3]).collect();
+    let messages: Vec<&[u8]> = records.iter().map(Vec::as_slice).collect();
+
+    let digests = mirror_hash::hash_many(&messages);
+    assert_eq!(digests.len(), messages.len());
+    for (digest, message) in digests.iter().zip(&messages) {
+        assert_eq!(digest, &Mirror256::new_from_bytes(message).finalize());
+    }
+    assert!(mirror_hash::hash_many(&[]).is_empty());
+}
+
+#[test]
+fn test_hash_many_uses_hasher_configuration() {
+    let mut prefixed = Mirror256::builder().depth(16).size(224).personalization(b"records").build().unwrap();
+    prefixed.update(b"shared prefix");
+    let messages: [&[u8]; 5] = [b"", b"a", &[9; 28], &[3; 29], &[0; 100]];
+
+    for (digest, message) in prefixed.hash_many(&messages).iter().zip(messages) {
+        let mut single = prefixed.clone();
+        single.update(message);
+        assert_eq!(digest, &single.finalize());
+    }
+}
+
+#[test]
+fn test_compression_test_vectors() {
+    let vectors = [
+        (Compression::MatyasMeyerOseas, "", "0x9707040180b949c17c424a7cd727577b21592e52aa5f14e799a6bd427b82a2bb"),
+        (Compression::MatyasMeyerOseas, "abc", "0xbea8e93e826c542163124a7cd727577b21592e52aa5f14e799a6be254e8b1356"),
+        (Compression::MiyaguchiPreneel, "", "0xd58d2b995791e7e34198fd6969025a7709d63e7bbc6eef27f826b18114207f81"),
+        (Compression::MiyaguchiPreneel, "abc", "0xfc22c6a65544fa035ec8fd6969025a7709d63e7bbc6eef27f826b2e62129ce6c"),
+    ];
+    for (compression, input, expected) in vectors {
+        let mut hasher = Mirror256::builder().compression(compression).build().unwrap();
+        hasher.update_str(input);
+        assert_eq!(hasher.hexdigest(), expected, "{:?} {:?}", compression, input);
+    }
+
+    assert_eq!(Compression::default(), Compression::Permutation);
+    assert_eq!(
+        Mirror256::builder().compression(Compression::Permutation).build().unwrap().hexdigest(),
+        Mirror256::new().hexdigest()
+    );
+}
+
+#[test]
+fn test_feed_forward_of_one_block() {
+    let mut padded = b"abc".to_vec();
+    padded.push(0x80);
+    padded.resize(24, 0);
+    padded.extend_from_slice(&24u64.to_be_bytes());
+
+    for compression in [Compression::MatyasMeyerOseas, Compression::MiyaguchiPreneel] {
+        let hasher = Mirror256::builder().depth(16).compression(compression).build().unwrap();
+        let permutation = hasher.block_permutation();
+        let mut output = padded.clone();
+        permutation.forward(&mut output);
+
+        // Inverting the permutation alone no longer recovers the block from the digest
+        let mut digest = hasher.clone();
+        digest.update(b"abc");
+        let digest = digest.finalize();
+        let mut inverted = digest.clone();
+        permutation.inverse(&mut inverted);
+        assert_ne!(inverted, padded);
+
+        let chaining = &permutation.encodings()[0];
+        let expected: Vec<u8> = match compression {
+            Compression::MiyaguchiPreneel => (0..32).map(|i| output[i] ^ padded[i] ^ chaining[i]).collect(),
+            _ => (0..32).map(|i| output[i] ^ padded[i]).collect(),
+        };
+        assert_eq!(digest, expected);
+    }
+}
+
+#[test]
+fn test_message_dependent_schedule_test_vectors() {
+    let vectors = [
+        (128, "", "0xa72d01fe34f37677a7c53c1859610bb9ab984700197ea75f0b36355dc0e489c5"),
+        (128, "abc", "0xa9b2aa4b8564c1262fb1fe74c75ee39a6c99a3fc32330f02c7621cf4632cbc07"),
+        (
+            128,
+            "The quick brown fox jumps over the lazy dog",
+            "0xbe8f245f9424eb7c8bcea27651e5b00c78a80c0dfa33115835ecf087706a1f52",
+        ),
+        (16, "abc", "0x1d1161be4dea69e5fdcf0084e7310efacfc9616c8bdd47146f23e5aa49ea50ed"),
+    ];
+    for (depth, input, expected) in vectors {
+        let mut hasher = Mirror256::builder().depth(depth).key_schedule(KeySchedule::MessageDependent).build().unwrap();
+        hasher.update_str(input);
+        assert_eq!(hasher.hexdigest(), expected, "{:?}", input);
+    }
+
+    assert_eq!(KeySchedule::default(), KeySchedule::Chained);
+    assert_eq!((KeySchedule::Chained.version(), KeySchedule::MessageDependent.version()), (1, 2));
+    assert_eq!(
+        Mirror256::builder().key_schedule(KeySchedule::Chained).build().unwrap().hexdigest(),
+        Mirror256::new().hexdigest()
+    );
+}
+
+#[test]
+fn test_message_dependent_first_block_layers() {
+    // Nibble n of a block is hex digit n, so rotating by n nibbles rotates the hex by n digits
+    let rotate = |block: &[u8], l: usize| -> Vec<u8> {
+        let mut digits: Vec<u8> = hex::encode(block).into_bytes();
+        let len = digits.len();
+        digits.rotate_right(l % len);
+        hex::decode(digits).unwrap()
+    };
+
+    let mut padded = b"abc".to_vec();
+    padded.push(0x80);
+    padded.resize(24, 0);
+    padded.extend_from_slice(&24u64.to_be_bytes());
+
+    let hasher = Mirror256::builder().depth(16).key_schedule(KeySchedule::MessageDependent).build().unwrap();
+    // Block 0 has an all-zero counter block
+    let encodings: Vec<Vec<u8>> = hasher
+        .block_permutation()
+        .encodings()
+        .iter()
+        .enumerate()
+        .map(|(l, chained)| chained.iter().zip(rotate(&padded, l + 1)).map(|(c, m)| c ^ m).collect())
+        .collect();
+    let encodings: Vec<&[u8]> = encodings.iter().map(Vec::as_slice).collect();
+    let mut expected = padded.clone();
+    BlockPermutation::new(256, &encodings).unwrap().forward(&mut expected);
+
+    let mut digest = hasher;
+    digest.update(b"abc");
+    assert_eq!(digest.finalize(), expected);
+}
+
+#[test]
+fn test_message_dependent_separates_blocks_at_low_depth() {
+    // 23 bytes and the 9 bytes of padding fill one block, so the digest is the
+    // permutation of that block under its own layers; with a layer that cancels the
+    // block, one-byte changes to a zero message collide with it by the thousand
+    for depth in 1..=3 {
+        let hasher = Mirror256::builder().depth(depth).key_schedule(KeySchedule::MessageDependent).build().unwrap();
+        let digest = |message: &[u8]| {
+            let mut h = hasher.clone();
+            h.update(message);
+            h.finalize()
+        };
+        let zero = digest(&[0; 23]);
+        for i in 0..23 {
+            for value in 1..=255 {
+                let mut message = [0; 23];
+                message[i] = value;
+                assert_ne!(digest(&message), zero, "depth {} byte {} value {}", depth, i, value);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_message_dependent_schedule_is_consistent() {
+    let data: Vec<u8> = (0..200u8).collect();
+    let builder = || depth16_builder().key_schedule(KeySchedule::MessageDependent);
+    let mut one_shot = builder().build().unwrap();
+    one_shot.update(&data);
+    let expected = one_shot.finalize();
+
+    for split in [1, 31, 32, 33, 100] {
+        let mut hasher = builder().build().unwrap();
+        hasher.update(&data[..split]);
+        hasher.update(&data[split..]);
+        assert_eq!(hasher.finalize(), expected, "split at {}", split);
+    }
+
+    // Batched hashing agrees with single messages
+    let hasher = builder().build().unwrap();
+    let messages: Vec<&[u8]> = vec![b"", b"abc", &data];
+    let singles: Vec<Vec<u8>> = messages
+        .iter()
+        .map(|m| {
+            let mut single = hasher.clone();
+            single.update(m);
+            single.finalize()
+        })
+        .collect();
+    assert_eq!(hasher.hash_many(&messages), singles);
+
+    // Equal blocks at different positions run different circuits
+    let mut chained = depth16();
+    let mut dependent = builder().build().unwrap();
+    chained.update(&[7; 64]);
+    dependent.update(&[7; 64]);
+    assert_ne!(chained.finalize(), dependent.finalize());
+}
+
+#[test]
+fn test_wide_pipe_test_vectors() {
+    let cases: [(usize, usize, &[u8], &str); 4] = [
+        (512, 256, b"", "b817d5b5ef23815952cf3a1fc6cdcc86a5cd02002c75e2b68c0ac49120faec7f"),
+        (512, 256, b"abc", "a66dc38717266c35a1c1b4e1c7ef3f552b631da60813b6d2d05812422f520fc0"),
+        (
+            384,
+            256,
+            b"The quick brown fox jumps over the lazy dog",
+            "8f4d86360c87fc46e206d9c7cb4bdf3eee3200aff54141fdeeb45498f29c3295",
+        ),
+        (256, 128, b"abc", "08f409f55151f5a8da7b29dfd517090a"),
+    ];
+    for (size, output_size, message, expected) in cases {
+        let mut hasher = Mirror256::builder().size(size).output_size(output_size).build().unwrap();
+        assert_eq!(hasher.output_size(), output_size / 8);
+        hasher.update(message);
+        assert_eq!(hasher.hexdigest(), format!("0x{}", expected), "{} bits to {}", size, output_size);
+    }
+}
+
+#[test]
+fn test_wide_pipe_digests_are_not_truncated_chaining_values() {
+    let wide = |output_size: usize| depth16_builder().size(512).output_size(output_size).build().unwrap();
+
+    // The full output size is the plain digest
+    let mut full = wide(512);
+    full.update(b"abc");
+    let full = full.finalize();
+    let mut plain = depth16_builder().size(512).build().unwrap();
+    plain.update(b"abc");
+    assert_eq!(full, plain.finalize());
+
+    // Shorter digests are not prefixes of it, nor of each other
+    let mut half = wide(256);
+    half.update(b"abc");
+    let half = half.finalize();
+    let mut quarter = wide(128);
+    quarter.update(b"abc");
+    let quarter = quarter.finalize();
+    assert_eq!((half.len(), quarter.len()), (32, 16));
+    assert_ne!(half[..], full[..32]);
+    assert_ne!(quarter[..], half[..16]);
+
+    // Streaming and batched hashing agree with one-shot hashing
+    let data: Vec<u8> = (0..150u8).collect();
+    let mut one_shot = wide(256);
+    one_shot.update(&data);
+    let expected = one_shot.finalize();
+    let mut split = wide(256);
+    split.update(&data[..70]);
+    split.update(&data[70..]);
+    assert_eq!(split.finalize(), expected);
+    assert_eq!(wide(256).hash_many(&[b"abc", &data]), vec![half, expected]);
+}
+
+#[test]
+fn test_wide_pipe_rejects_invalid_output_sizes() {
+    assert_eq!(
+        Mirror256::builder().output_size(100).build().err(),
+        Some(Mirror256Error::OutputSizeNotMultipleOf8(100))
+    );
+    assert_eq!(
+        Mirror256::builder().output_size(56).build().err(),
+        Some(Mirror256Error::OutputSizeTooSmall { bits: 56, min: MIN_OUTPUT_SIZE })
+    );
+    assert_eq!(
+        Mirror256::builder().output_size(264).build().err(),
+        Some(Mirror256Error::OutputSizeTooLarge { bits: 264, max: 256 })
+    );
+    assert!(Mirror256::builder().output_size(MIN_OUTPUT_SIZE).build().is_ok());
+}
+
+#[test]
+fn test_seeded_state_test_vectors() {
+    let vectors = [
+        (DEFAULT_SEED, 128, 256, "abc", "0x0030be2a5589a4c7547c49cc29006353c8eb004df4415e90c3f3a232c79f2750"),
+        (0, 128, 256, "", "0x524c5670f3c68bd35e68d6fe6caea6707fe3145e9dba10cb0800bca245405ca1"),
+        (1, 16, 224, "abc", "0xbf2e4bc63247e75b4a116ca101216fe0863aa718e4575037ffd24f1e"),
+    ];
+    for (seed, depth, size, input, expected) in vectors {
+        let mut hasher =
+            Mirror256::builder().initial_state(InitialState::Seeded(seed)).depth(depth).size(size).build().unwrap();
+        hasher.update_str(input);
+        assert_eq!(hasher.hexdigest(), expected, "seed {}", seed);
+    }
+}
+
+#[test]
+fn test_seeded_state_follows_splitmix64() {
+    // SplitMix64 seeded with 0 starts with 0xe220a8397b1dcdaf; its nibbles fill the
+    // wires from the least significant end, two per byte with the high nibble first
+    let hasher = Mirror256::builder().initial_state(InitialState::Seeded(0)).build().unwrap();
+    let encodings = hasher.block_permutation().encodings();
+    assert_eq!(encodings[0][..8], [0xfa, 0xdc, 0xd1, 0xb7, 0x93, 0x8a, 0x02, 0x2e]);
+
+    let other = Mirror256::builder().initial_state(InitialState::Seeded(1)).build().unwrap();
+    assert_ne!(hasher.hexdigest(), other.hexdigest());
+}
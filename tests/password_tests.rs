@@ -0,0 +1,142 @@
+use mirror_hash::password::{self, hash_password, verify_password, PasswordHash, PasswordParams};
+use mirror_hash::Mirror256Error;
+
+/// Password, salt, parameters and the PHC string they hash to
+#[test]
+fn test_known_answers() {
+    let cases = [
+        (
+            &b"password"[..],
+            &b"saltsaltsaltsalt"[..],
+            (16, 64, 1),
+            "$mirror256$d=16,m=64,t=1$c2FsdHNhbHRzYWx0c2FsdA$iBYeIICWzM1nzeoAXjSgYa1Q6+wVzvUT2zs4JIedwXs",
+        ),
+        (
+            b"",
+            b"NaClNaCl",
+            (32, 128, 2),
+            "$mirror256$d=32,m=128,t=2$TmFDbE5hQ2w$avnR/rXflHXW58GKrxe0/GGU5/pAyz1yoiyCBX+ZCd4",
+        ),
+        (
+            b"correct horse battery staple",
+            b"0123456789abcdef",
+            (128, 256, 3),
+            "$mirror256$d=128,m=256,t=3$MDEyMzQ1Njc4OWFiY2RlZg$goyC/Pu4vEdYHvI1pxYCI+xeDYi9Bu65kkK517Bqbt4",
+        ),
+    ];
+    for (password, salt, (d, m, t), expected) in cases {
+        let params = PasswordParams::new(d, m, t).unwrap();
+        assert_eq!(hash_password(password, salt, &params).unwrap(), expected);
+        assert_eq!(verify_password(password, expected, &params), Ok(()));
+    }
+}
+
+#[test]
+fn test_wrong_password_is_rejected() {
+    let params = PasswordParams::new(16, 64, 1).unwrap();
+    let stored = hash_password(b"hunter2", b"saltsaltsaltsalt", &params).unwrap();
+    assert_eq!(verify_password(b"hunter3", &stored, &params), Err(Mirror256Error::PasswordMismatch));
+    assert_eq!(verify_password(b"", &stored, &params), Err(Mirror256Error::PasswordMismatch));
+}
+
+#[test]
+fn test_every_parameter_changes_the_hash() {
+    let params = PasswordParams::new(16, 64, 1).unwrap();
+    let base = password::derive(b"pw", b"saltsaltsaltsalt", &params).unwrap();
+    let variants = [
+        password::derive(b"pw", b"saltsaltsaltsalT", &params).unwrap(),
+        password::derive(b"pw", b"saltsaltsaltsalt", &PasswordParams::new(17, 64, 1).unwrap()).unwrap(),
+        password::derive(b"pw", b"saltsaltsaltsalt", &PasswordParams::new(16, 65, 1).unwrap()).unwrap(),
+        password::derive(b"pw", b"saltsaltsaltsalt", &PasswordParams::new(16, 64, 2).unwrap()).unwrap(),
+    ];
+    for variant in variants {
+        assert_ne!(variant, base);
+    }
+}
+
+#[test]
+fn test_phc_strings_roundtrip() {
+    let params = PasswordParams::new(16, 64, 1).unwrap();
+    let stored = hash_password(b"pw", b"0123456789", &params).unwrap();
+    let parsed = PasswordHash::parse(&stored).unwrap();
+    assert_eq!(parsed.params, params);
+    assert_eq!(parsed.salt, b"0123456789");
+    assert_eq!(parsed.hash.len(), 32);
+    assert_eq!(parsed.to_string(), stored);
+}
+
+#[test]
+fn test_malformed_strings_are_rejected() {
+    let hash = "iBYeIICWzM1nzeoAXjSgYa1Q6+wVzvUT2zs4JIedwXs";
+    let malformed = [
+        String::new(),
+        format!("mirror256$d=16,m=64,t=1$c2FsdHNhbHRzYWx0c2FsdA${}", hash),
+        format!("$argon2id$d=16,m=64,t=1$c2FsdHNhbHRzYWx0c2FsdA${}", hash),
+        format!("$mirror256$m=64,d=16,t=1$c2FsdHNhbHRzYWx0c2FsdA${}", hash),
+        format!("$mirror256$d=16,m=64$c2FsdHNhbHRzYWx0c2FsdA${}", hash),
+        format!("$mirror256$d=016,m=64,t=1$c2FsdHNhbHRzYWx0c2FsdA${}", hash),
+        format!("$mirror256$d=+16,m=64,t=1$c2FsdHNhbHRzYWx0c2FsdA${}", hash),
+        format!("$mirror256$d=16,m=64,t=1$c2FsdHNhbHRzYWx0c2FsdA==${}", hash),
+        format!("$mirror256$d=16,m=64,t=1$c2FsdHNhbHRzYWx0c2FsdA${}AA", hash),
+        format!("$mirror256$d=16,m=64,t=1$c2FsdHNhbHRzYWx0c2FsdA${}$", hash),
+    ];
+    for phc in malformed {
+        assert_eq!(PasswordHash::parse(&phc), Err(Mirror256Error::MalformedPasswordHash), "{}", phc);
+        assert_eq!(verify_password(b"password", &phc, &PasswordParams::default()), Err(Mirror256Error::MalformedPasswordHash));
+    }
+}
+
+#[test]
+fn test_parameters_are_checked() {
+    let params = PasswordParams::new(16, 64, 1).unwrap();
+    assert_eq!(PasswordParams::new(0, 64, 1), Err(Mirror256Error::ZeroDepth));
+    assert!(matches!(
+        PasswordParams::new(16, 0, 1),
+        Err(Mirror256Error::PasswordParameterOutOfRange { name: "m", value: 0, .. })
+    ));
+    assert!(matches!(
+        PasswordParams::new(16, 64, 0),
+        Err(Mirror256Error::PasswordParameterOutOfRange { name: "t", value: 0, .. })
+    ));
+    assert_eq!(
+        hash_password(b"pw", b"short", &params),
+        Err(Mirror256Error::SaltTooShort { len: 5, min: password::MIN_SALT_BYTES })
+    );
+    assert_eq!(
+        hash_password(b"pw", &[0; 65], &params),
+        Err(Mirror256Error::SaltTooLong { len: 65, max: password::MAX_SALT_BYTES })
+    );
+
+    // Parameters from a stored string are held to the same limits
+    let phc = "$mirror256$d=16,m=0,t=1$c2FsdHNhbHRzYWx0c2FsdA$iBYeIICWzM1nzeoAXjSgYa1Q6+wVzvUT2zs4JIedwXs";
+    assert!(matches!(verify_password(b"password", phc, &PasswordParams::default()), Err(Mirror256Error::PasswordParameterOutOfRange { .. })));
+}
+
+#[test]
+fn test_verification_rejects_costs_over_the_limit() {
+    let salt = "c2FsdHNhbHRzYWx0c2FsdA";
+    let hash = "iBYeIICWzM1nzeoAXjSgYa1Q6+wVzvUT2zs4JIedwXs";
+    let max_cost = PasswordParams::new(16, 64, 1).unwrap();
+    let cases = [
+        ("d", "d=512,m=64,t=1", 512, 16),
+        ("m", "d=16,m=16777216,t=1", 1 << 24, 64),
+        ("t", "d=16,m=64,t=65536", 1 << 16, 1),
+    ];
+    for (name, params, value, max) in cases {
+        // Rejected before any hashing, so this returns at once
+        let phc = format!("$mirror256${}${}${}", params, salt, hash);
+        assert_eq!(
+            verify_password(b"password", &phc, &max_cost),
+            Err(Mirror256Error::PasswordParameterOutOfRange { name, value, min: 1, max })
+        );
+    }
+
+    let phc = format!("$mirror256$d=16,m=64,t=1${}${}", salt, hash);
+    assert_eq!(verify_password(b"password", &phc, &max_cost), Ok(()));
+}
+
+#[test]
+fn test_default_parameters() {
+    let params = PasswordParams::default();
+    assert_eq!((params.depth(), params.memory(), params.passes()), (128, 4096, 3));
+}
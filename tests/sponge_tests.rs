@@ -0,0 +1,131 @@
+use mirror_hash::{Mirror256, Mirror256Error, MirrorDuplex, MirrorSponge};
+
+fn sponge_digest(input: &[u8]) -> String {
+    let mut sponge = MirrorSponge::new();
+    sponge.update(input);
+    hex::encode(sponge.finalize())
+}
+
+#[test]
+fn test_known_answers() {
+    assert_eq!(sponge_digest(b""), "33886442c93fa4c603eb9b4a8f600350e9b50d9987c46001284ef18d1b0d845a");
+    assert_eq!(sponge_digest(b"abc"), "09eea229467527859567c3163ed85cf1ae0d766f88393e29ed62cfb9b42a9c6d");
+    assert_eq!(sponge_digest(&[b'a'; 100]), "681d06383ab82efbc66c0fb8fdce597e50466800544ed14ed551da3d7742c89f");
+
+    // 512-bit state, 32 layers, 256-bit capacity
+    let permutation = Mirror256::builder().depth(32).size(512).build().unwrap().block_permutation();
+    let mut sponge = MirrorSponge::with_permutation(permutation, 256).unwrap();
+    assert_eq!((sponge.rate(), sponge.capacity(), sponge.output_size()), (32, 256, 64));
+    sponge.update(b"abc");
+    assert_eq!(
+        hex::encode(sponge.finalize_xof().squeeze(100)),
+        "6080c4fd54420fed37f640f14a6bd1f9af7ff0cb1c3aac88992488c65748c2b7a89aa3649445379f20cc179a6ee9c250\
+         08ee391020d174faf9c86a057360d241e18578d38fdbdf8bacb47100f2b095ed27b36f279a7bbf24f8b01f471707a764\
+         0d91e86a"
+    );
+}
+
+#[test]
+fn test_incremental_updates_match_one_shot() {
+    let data: Vec<u8> = (0..200u8).collect();
+    let expected = sponge_digest(&data);
+    for split in [0, 1, 15, 16, 17, 100, 199, 200] {
+        let mut sponge = MirrorSponge::new();
+        sponge.update(&data[..split]);
+        sponge.update(&data[split..]);
+        assert_eq!(hex::encode(sponge.finalize()), expected, "split at {}", split);
+    }
+}
+
+#[test]
+fn test_digest_is_a_prefix_of_the_output_stream() {
+    let mut sponge = MirrorSponge::new();
+    sponge.update(b"stream");
+    let digest = sponge.digest();
+    let stream = sponge.finalize_xof().squeeze(500);
+    assert_eq!(&stream[..32], digest.as_slice());
+}
+
+#[test]
+fn test_padding_separates_messages() {
+    // A message and the same message with its padding bytes appended
+    let mut padded = b"abc".to_vec();
+    padded.push(0x01);
+    padded.resize(15, 0);
+    padded.push(0x80);
+    assert_ne!(sponge_digest(b"abc"), sponge_digest(&padded));
+    assert_ne!(sponge_digest(b""), sponge_digest(&[0]));
+    assert_ne!(sponge_digest(&[0; 15]), sponge_digest(&[0; 16]));
+}
+
+#[test]
+fn test_similar_messages_have_unrelated_digests() {
+    for i in 0..20 {
+        let a = sponge_digest(format!("hello world {}", i).as_bytes());
+        let b = sponge_digest(format!("hello world {}", i + 1).as_bytes());
+        let (a, b) = (hex::decode(a).unwrap(), hex::decode(b).unwrap());
+        let shared = a.iter().zip(&b).filter(|(x, y)| x == y).count();
+        let distance: u32 = a.iter().zip(&b).map(|(x, y)| (x ^ y).count_ones()).sum();
+        assert!(shared <= 4, "{} equal bytes after message {}", shared, i);
+        assert!((80..=176).contains(&distance), "digests {} bits apart after message {}", distance, i);
+    }
+}
+
+#[test]
+fn test_sponge_differs_from_chaining_construction() {
+    assert_ne!(MirrorSponge::new().digest(), Mirror256::new().digest());
+}
+
+#[test]
+fn test_duplex_outputs_match_the_sponge_of_padded_inputs() {
+    let inputs: [&[u8]; 4] = [b"key", b"", b"nonce value", &[7; 15]];
+    let mut duplex = MirrorDuplex::new();
+    assert_eq!((duplex.rate(), duplex.max_input()), (16, 15));
+
+    let mut history = Vec::new();
+    for input in inputs {
+        let mut output = [0u8; 16];
+        duplex.duplex(input, &mut output);
+
+        let mut sponge = MirrorSponge::new();
+        sponge.update(&history);
+        sponge.update(input);
+        assert_eq!(output.to_vec(), sponge.finalize_xof().squeeze(16));
+
+        // Earlier inputs enter later calls padded to a full rate block
+        let mut block = input.to_vec();
+        block.push(0x01);
+        block.resize(16, 0);
+        block[15] ^= 0x80;
+        history.extend(block);
+    }
+}
+
+#[test]
+fn test_duplex_known_answers() {
+    let mut duplex = MirrorDuplex::new();
+    let mut output = [0u8; 16];
+    duplex.duplex(b"key", &mut output);
+    assert_eq!(hex::encode(output), "4689afbaa6a11a9de712d1073f4f7d6d");
+    duplex.duplex(b"", &mut output);
+    assert_eq!(hex::encode(output), "0662e675528f8a8916c99c2be390f0c8");
+}
+
+#[test]
+#[should_panic(expected = "duplex input must leave room for padding")]
+fn test_duplex_rejects_full_rate_input() {
+    MirrorDuplex::new().duplex(&[0; 16], &mut []);
+}
+
+#[test]
+fn test_capacity_is_checked() {
+    let permutation = Mirror256::new().block_permutation();
+    for capacity in [0, 4, 129, 248, 256, 264] {
+        assert_eq!(
+            MirrorSponge::with_permutation(permutation.clone(), capacity).unwrap_err(),
+            Mirror256Error::SpongeCapacity { capacity, size: 256 }
+        );
+    }
+    assert_eq!(MirrorSponge::with_permutation(permutation.clone(), 240).unwrap().rate(), 2);
+    assert_eq!(MirrorDuplex::with_permutation(permutation, 8).unwrap().max_input(), 30);
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::depth16;
+use mirror_hash::{Digest, Mirror256, Mirror256Error, TreeHasher, DEFAULT_CHUNK_SIZE};
+
+fn data(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 7 + i / 251) as u8).collect()
+}
+
+fn tree_digest(chunk_size: usize, input: &[u8]) -> Digest {
+    let mut tree = TreeHasher::new(&depth16(), chunk_size).unwrap();
+    tree.update(input);
+    tree.finalize()
+}
+
+/// The tree construction spelled out with plain hashes
+fn expected_tree_digest(chunk_size: usize, input: &[u8]) -> Digest {
+    let node = |prefix: u8, body: &[u8]| {
+        let mut hasher = depth16();
+        hasher.update(&[prefix]);
+        hasher.update(body);
+        hasher.finalize()
+    };
+
+    let mut nodes: Vec<Digest> = if input.is_empty() {
+        vec![node(0x00, b"")]
+    } else {
+        input.chunks(chunk_size).map(|chunk| node(0x00, chunk)).collect()
+    };
+    while nodes.len() > 1 {
+        let mut next: Vec<Digest> = nodes.chunks_exact(2).map(|pair| node(0x01, &pair.concat())).collect();
+        if nodes.len() % 2 == 1 {
+            next.push(nodes.last().unwrap().clone());
+        }
+        nodes = next;
+    }
+    nodes.pop().unwrap()
+}
+
+#[test]
+fn test_tree_matches_construction() {
+    for len in [0, 1, 99, 100, 101, 300, 500, 700, 1234] {
+        let input = data(len);
+        assert_eq!(tree_digest(100, &input), expected_tree_digest(100, &input), "length {}", len);
+    }
+}
+
+#[test]
+fn test_tree_is_split_invariant() {
+    let input = data(100 * 64 * 3 + 57);
+    let expected = tree_digest(100, &input);
+
+    for piece in [1, 7, 100, 4096, 6400, 6401, 20_000] {
+        let mut tree = TreeHasher::new(&depth16(), 100).unwrap();
+        for chunk in input.chunks(piece) {
+            tree.update(chunk);
+        }
+        assert_eq!(tree.digest(), expected, "pieces of {} bytes", piece);
+        assert_eq!(tree.finalize(), expected);
+    }
+}
+
+#[test]
+fn test_tree_differs_from_plain_digest_and_chunk_size() {
+    let input = data(1000);
+    let mut plain = depth16();
+    plain.update(&input);
+
+    assert_ne!(tree_digest(100, &input), plain.finalize());
+    assert_ne!(tree_digest(100, &input), tree_digest(200, &input));
+    assert_ne!(tree_digest(100, &input), tree_digest(100, &input[..999]));
+}
+
+#[test]
+fn test_tree_rejects_zero_chunk_size() {
+    assert_eq!(TreeHasher::new(&Mirror256::new(), 0).err(), Some(Mirror256Error::ZeroChunkSize));
+    assert_eq!(TreeHasher::default().chunk_size(), DEFAULT_CHUNK_SIZE);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_parallel_tree_matches_sequential() {
+    let input = data(64 * 64 * 5 + 3);
+    for chunk_size in [64, 1000] {
+        let mut parallel = TreeHasher::new(&depth16(), chunk_size).unwrap();
+        let mut sequential = TreeHasher::new(&depth16(), chunk_size).unwrap().parallel(false);
+        parallel.update(&input);
+        sequential.update(&input);
+
+        assert_eq!(parallel.finalize(), sequential.finalize());
+    }
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_par_hash_many_matches_hash_many() {
+    let records: Vec<Vec<u8>> = (0..101).map(|i| data(i * 5)).collect();
+    let messages: Vec<&[u8]> = records.iter().map(Vec::as_slice).collect();
+
+    assert_eq!(mirror_hash::par_hash_many(&messages), mirror_hash::hash_many(&messages));
+    let hasher = depth16();
+    assert_eq!(hasher.par_hash_many(&messages), hasher.hash_many(&messages));
+    assert!(hasher.par_hash_many(&[]).is_empty());
+}
@@ -0,0 +1,118 @@
+use mirror_hash::{Mirror128, Mirror224, Mirror256, Mirror256_128, Mirror256_160, Mirror256_64, Mirror384, Mirror512};
+
+#[test]
+fn test_known_answers_empty() {
+    assert_eq!(Mirror128::new_from_bytes(b"").hexdigest(), "0x01a467aeeb7b49c17c424a7cd72755d5");
+    assert_eq!(
+        Mirror224::new_from_bytes(b"").hexdigest(),
+        "0xdfbb0f5080b949c17c424a7cd727577b21592e5ad14557244e5cdd94"
+    );
+    assert_eq!(
+        Mirror256::new_from_bytes(b"").hexdigest(),
+        "0x1707040180b949c17c424a7cd727577b21592e52aa5f14e799a6bd427b82a2bb"
+    );
+    assert_eq!(
+        Mirror384::new_from_bytes(b"").hexdigest(),
+        "0x6986323180b949c17c424a7cd727577b21592e52aa5f14e799e00889c17098b40c5c4920f662b7303a5f96d56534f9a7"
+    );
+    assert_eq!(
+        Mirror512::new_from_bytes(b"").hexdigest(),
+        "0x97e4750180b949c17c424a7cd727577b21592e52aa5f14e799e00889c17098b4\
+         0c5c4920f662b7303a5f9698cc1b0e8b0203a30dbb2bdfde2bc42a67cc3efaa1"
+    );
+}
+
+#[test]
+fn test_known_answers_abc() {
+    assert_eq!(Mirror128::new_from_bytes(b"abc").hexdigest(), "0x6efcc477826c542163124afeb9b8c9af");
+    assert_eq!(
+        Mirror224::new_from_bytes(b"abc").hexdigest(),
+        "0x462b73ae826c542163124a7cd727577b21592e52aa55b9fa32287fde"
+    );
+    assert_eq!(
+        Mirror256::new_from_bytes(b"abc").hexdigest(),
+        "0xdfca8abe826c542163124a7cd727577b21592e52aa5f14e799a6be254e8b134e"
+    );
+    assert_eq!(
+        Mirror384::new_from_bytes(b"abc").hexdigest(),
+        "0xe6480b018ec5b52163124a7cd727577b21592e52aa5f14e799e00889c17098b40c5c4920f66234a2998693145938297f"
+    );
+    assert_eq!(
+        Mirror512::new_from_bytes(b"abc").hexdigest(),
+        "0x6517a177826c542163124a7cd727577b21592e52aa5f14e799e00889c17098b4\
+         0c5c4920f662b7303a5f9698cc1b0e8b0203a30dbb2bdfded0a4457b20487546"
+    );
+}
+
+#[test]
+fn test_lengths_derive_from_width() {
+    assert_eq!(Mirror128::new().finalize().len(), Mirror128::OUTPUT_SIZE);
+    assert_eq!(Mirror224::new().finalize().len(), 28);
+    assert_eq!(Mirror384::new().finalize().len(), 48);
+    assert_eq!(Mirror512::new().finalize().len(), 64);
+    
+    let hasher = Mirror256::builder().size(224).build().unwrap();
+    assert_eq!(hasher.block_size(), Mirror224::BLOCK_SIZE);
+    assert_eq!(hasher.output_size(), Mirror224::OUTPUT_SIZE);
+}
+
+#[test]
+fn test_variant_matches_builder() {
+    let mut hasher = Mirror256::builder().size(384).build().unwrap();
+    hasher.update(b"configured through the builder");
+    
+    assert_eq!(
+        hasher.hexdigest(),
+        Mirror384::new_from_bytes(b"configured through the builder").hexdigest()
+    );
+}
+
+#[test]
+fn test_blocks_follow_width() {
+    // A 28-byte message fills exactly one Mirror224 block, so every split agrees
+    let input: Vec<u8> = (0..90u8).collect();
+    let expected = Mirror224::new_from_bytes(&input).hexdigest();
+    
+    for split in [1, 27, 28, 29, 56, 57, 89] {
+        let mut hasher = Mirror224::new();
+        hasher.update(&input[..split]);
+        hasher.update(&input[split..]);
+        assert_eq!(hasher.hexdigest(), expected, "split at {}", split);
+    }
+    
+    // Bytes beyond the first 32 of a block are part of the message
+    let mut changed = input.clone();
+    changed[40] ^= 1;
+    assert_ne!(Mirror512::new_from_bytes(&input[..50]).hexdigest(), Mirror512::new_from_bytes(&changed[..50]).hexdigest());
+}
+
+#[test]
+fn test_truncated_known_answers() {
+    assert_eq!(Mirror256_160::new_from_bytes(b"").hexdigest(), "0x9c894db1aae717bf93934c73742f295a7f3922bf");
+    assert_eq!(Mirror256_128::new_from_bytes(b"").hexdigest(), "0xd6d0d7b1013b694032c098f7435e79ae");
+    assert_eq!(Mirror256_64::new_from_bytes(b"").hexdigest(), "0x7fc330c9210c31ec");
+
+    assert_eq!(Mirror256_160::new_from_bytes(b"abc").hexdigest(), "0xf1382a743d54ba9b8fd1702ebd5a2af6e5f92ad8");
+    assert_eq!(Mirror256_128::new_from_bytes(b"abc").hexdigest(), "0x08f409f55151f5a8da7b29dfd517090a");
+    assert_eq!(Mirror256_64::new_from_bytes(b"abc").hexdigest(), "0x6a64545f326cfae0");
+}
+
+#[test]
+fn test_truncated_variants_are_not_prefixes() {
+    let full = Mirror256::new_from_bytes(b"identifier").finalize();
+    let d160 = Mirror256_160::new_from_bytes(b"identifier").finalize();
+    let d128 = Mirror256_128::new_from_bytes(b"identifier").finalize();
+    let d64 = Mirror256_64::new_from_bytes(b"identifier").finalize();
+    assert_eq!((d160.len(), d128.len(), d64.len()), (Mirror256_160::OUTPUT_SIZE, 16, 8));
+    assert_eq!(Mirror256_64::BLOCK_SIZE, 32);
+
+    assert_ne!(d160[..], full[..20]);
+    assert_ne!(d128[..], full[..16]);
+    assert_ne!(d128[..], d160[..16]);
+    assert_ne!(d64[..], d128[..8]);
+
+    // The variants are the builder's truncated hashers
+    let mut hasher = Mirror256::builder().output_size(128).build().unwrap();
+    hasher.update(b"identifier");
+    assert_eq!(hasher.finalize(), d128);
+}
@@ -0,0 +1,73 @@
+use mirror_hash::{Compression, Mirror128, Mirror256};
+use std::io::Read;
+
+#[test]
+fn test_known_answers() {
+    assert_eq!(
+        hex::encode(Mirror256::new().finalize_xof().squeeze(80)),
+        "85d74ab4a7aa20f286b59e1d2cf040787c76c219d08d2d77d5dc1e10e82b4bb84181f28663239f80\
+         2a6c44e49990dadb3643b5ed805a0a27a1402a3793ec7ca3214fcd661d142dc4cbee909bad600b77"
+    );
+    assert_eq!(
+        hex::encode(Mirror256::new_from_bytes(b"abc").finalize_xof().squeeze(80)),
+        "402bf948c4579a6e4de55d3fa765b2b40709e69e623bbd3490e40b086d57e94e5e75520d3da992b3\
+         1f1f1ee6faea612cb9a290bb7c665efbf5da24b38a6cf38acb9fbbfadbd206879805524cdb420625"
+    );
+    assert_eq!(
+        hex::encode(Mirror128::new_from_bytes(b"abc").finalize_xof().squeeze(40)),
+        "757fb9bbdd25b5f015c5daca6baa922d8f7c3dd1bec918963931af483bf4593e103dcf751b4746b3"
+    );
+}
+
+#[test]
+fn test_shorter_outputs_are_prefixes_of_longer_ones() {
+    let hasher = Mirror256::new_from_bytes(b"prefix property");
+    let long = hasher.clone().finalize_xof().squeeze(1000);
+    for len in [0, 1, 31, 32, 33, 64, 999] {
+        assert_eq!(hasher.clone().finalize_xof().squeeze(len), &long[..len]);
+    }
+}
+
+#[test]
+fn test_io_read_matches_squeeze() {
+    let hasher = Mirror256::new_from_bytes(b"reader");
+    let mut out = [0u8; 100];
+    hasher.clone().finalize_xof().read_exact(&mut out).unwrap();
+    assert_eq!(out.to_vec(), hasher.finalize_xof().squeeze(100));
+}
+
+#[test]
+fn test_output_depends_on_message_and_parameters() {
+    let xof = |hasher: Mirror256| hasher.finalize_xof().squeeze(64);
+    let base = xof(Mirror256::new_from_bytes(b"message"));
+    assert_ne!(xof(Mirror256::new_from_bytes(b"messagf")), base);
+    assert_ne!(xof(Mirror256::new_from_bytes(b"message\0")), base);
+
+    let mut keyed = Mirror256::builder().key(b"key").build().unwrap();
+    keyed.update(b"message");
+    assert_ne!(xof(keyed), base);
+
+    let mut fed_forward = Mirror256::builder().compression(Compression::MiyaguchiPreneel).build().unwrap();
+    fed_forward.update(b"message");
+    assert_ne!(xof(fed_forward), base);
+}
+
+#[test]
+fn test_output_of_nearby_messages_is_unrelated() {
+    for i in 0..20 {
+        let a = Mirror256::new_from_bytes(format!("seed {}", i).as_bytes()).finalize_xof().squeeze(96);
+        let b = Mirror256::new_from_bytes(format!("seed {}", i + 1).as_bytes()).finalize_xof().squeeze(96);
+        for (k, (x, y)) in a.chunks(32).zip(b.chunks(32)).enumerate() {
+            let distance: u32 = x.iter().zip(y).map(|(p, q)| (p ^ q).count_ones()).sum();
+            assert!((80..=176).contains(&distance), "block {} {} bits apart after message {}", k, distance, i);
+        }
+    }
+}
+
+#[test]
+fn test_output_differs_from_the_digest() {
+    let hasher = Mirror256::new_from_bytes(b"abc");
+    let digest = hasher.digest();
+    let stream = hasher.finalize_xof().squeeze(256);
+    assert!(stream.chunks(32).all(|block| block != digest.as_slice()));
+}